@@ -0,0 +1,255 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use regex::Regex;
+use syn::{
+    parse::ParseStream, parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput,
+    Error, Fields, Ident, LitStr, Path, Result, Token, Type,
+};
+
+// #[derive(FromRegex)]
+// #[regex(r"^#(?P<id>\d+) @ (?P<x>\d+),(?P<y>\d+)$", error = "ClaimError")]
+// struct Claim { id: u32, x: u32, #[regex(with = "parse_y", error = "YError")] y: u32 }
+//
+// Named fields are filled from the groups with the same name, tuple fields from
+// the numbered groups. Enums get a `regex` per variant and match the first one that fits.
+// The generated error enum has `FormatError` for lines that don't match and
+// `<Field>Error` (or `<Variant><Field>Error`) wrapping the error of the failing field.
+// The regexes are built through `aoc2018::__private`, so the deriving crate needs `aoc2018` but not `regex`.
+#[proc_macro_derive(FromRegex, attributes(regex))]
+pub fn derive_from_regex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct RegexAttr {
+    pattern: Option<LitStr>,
+    error: Option<LitStr>,
+    with: Option<LitStr>,
+}
+
+struct FieldParser {
+    label: String,
+    member: TokenStream2,
+    group: TokenStream2,
+    variant: Ident,
+    error_type: Type,
+    parse: TokenStream2,
+}
+
+fn parse_attr(attrs: &[Attribute]) -> Result<RegexAttr> {
+    let mut result = RegexAttr::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("regex")) {
+        attr.parse_args_with(|input: ParseStream| {
+            if input.peek(LitStr) {
+                result.pattern = Some(input.parse()?);
+                if input.is_empty() { return Ok(()); }
+                input.parse::<Token![,]>()?;
+            }
+            while !input.is_empty() {
+                let key: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                let value: LitStr = input.parse()?;
+                match key.to_string().as_str() {
+                    "error" => result.error = Some(value),
+                    "with" => result.with = Some(value),
+                    _ => return Err(Error::new(key.span(), "expected `error` or `with`")),
+                }
+                if input.is_empty() { break; }
+                input.parse::<Token![,]>()?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(result)
+}
+
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            first.to_string() + chars.as_str()
+        })
+        .collect()
+}
+
+fn check_pattern(pattern: &LitStr) -> Result<Regex> {
+    Regex::new(&pattern.value()).map_err(|e| Error::new(pattern.span(), e.to_string()))
+}
+
+fn field_parsers(fields: &Fields, regex: &Regex, pattern: &LitStr, prefix: &str) -> Result<Vec<FieldParser>> {
+    let names: Vec<&str> = regex.capture_names().flatten().collect();
+    let mut result = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attr = parse_attr(&field.attrs)?;
+        let ty = &field.ty;
+        let (member, group, suffix, label) = match &field.ident {
+            Some(ident) => {
+                let name = ident.to_string();
+                if !names.contains(&name.as_str()) {
+                    let message = format!("regex has no group named `{}`", name);
+                    return Err(Error::new(pattern.span(), message));
+                }
+                (quote!(#ident), quote!(caps.name(#name)), to_pascal_case(&name), name)
+            }
+            None => {
+                let index = syn::Index::from(i);
+                let group = i + 1;
+                if group >= regex.captures_len() {
+                    let message = format!("regex has no group number {}", group);
+                    return Err(Error::new(pattern.span(), message));
+                }
+                let (suffix, label) = match (fields.len(), prefix) {
+                    (_, "") => (i.to_string(), i.to_string()),
+                    (1, _) => (String::new(), prefix.to_string()),
+                    _ => (i.to_string(), format!("{}.{}", prefix, i)),
+                };
+                (quote!(#index), quote!(caps.get(#group)), suffix, label)
+            }
+        };
+        let variant = format_ident!("{}{}Error", prefix, suffix);
+        let (error_type, parse) = match (attr.with, attr.error) {
+            (Some(with), Some(error)) => {
+                let with: Path = with.parse()?;
+                (error.parse()?, quote!(#with(value)))
+            }
+            (Some(with), None) => {
+                return Err(Error::new(with.span(), "`with` needs an `error` type as well"));
+            }
+            (None, _) => {
+                let error_type = syn::parse_quote!(<#ty as ::std::str::FromStr>::Err);
+                (error_type, quote!(value.parse::<#ty>()))
+            }
+        };
+        result.push(FieldParser { label, member, group, variant, error_type, parse });
+    }
+    Ok(result)
+}
+
+fn build_value(constructor: TokenStream2, fields: &Fields, parsers: &[FieldParser], error: &Ident) -> TokenStream2 {
+    let assignments = parsers.iter().map(|FieldParser { member, group, variant, parse, .. }| {
+        quote! {
+            #member: {
+                let value = #group.map_or("", |m| m.as_str());
+                #parse.map_err(#error::#variant)?
+            }
+        }
+    });
+    let assignments: Vec<_> = assignments.collect();
+    match fields {
+        Fields::Unit => constructor,
+        _ => quote!(#constructor { #(#assignments),* }),
+    }
+}
+
+fn regex_static(pattern: &LitStr) -> TokenStream2 {
+    quote! {{
+        static REGEX: ::std::sync::OnceLock<::aoc2018::__private::Regex> = ::std::sync::OnceLock::new();
+        REGEX.get_or_init(|| ::aoc2018::__private::Regex::new(#pattern).unwrap())
+    }}
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let attr = parse_attr(&input.attrs)?;
+    let error = match &attr.error {
+        Some(error) => error.parse::<Ident>()?,
+        None => format_ident!("{}Error", name),
+    };
+
+    let mut parsers = Vec::new();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let pattern = attr.pattern.as_ref()
+                .ok_or_else(|| Error::new(Span::call_site(), "missing #[regex(\"...\")] on the struct"))?;
+            let regex = check_pattern(pattern)?;
+            parsers = field_parsers(&data.fields, &regex, pattern, "")?;
+            let regex = regex_static(pattern);
+            let value = build_value(quote!(Self), &data.fields, &parsers, &error);
+            quote! {
+                let caps = #regex.captures(s).ok_or(#error::FormatError)?;
+                Ok(#value)
+            }
+        }
+        Data::Enum(data) => {
+            let mut branches = Vec::new();
+            for variant in &data.variants {
+                let variant_attr = parse_attr(&variant.attrs)?;
+                let pattern = variant_attr.pattern.as_ref()
+                    .ok_or_else(|| Error::new(variant.span(), "missing #[regex(\"...\")] on the variant"))?;
+                let regex = check_pattern(pattern)?;
+                let ident = &variant.ident;
+                let variant_parsers = field_parsers(&variant.fields, &regex, pattern, &ident.to_string())?;
+                let regex = regex_static(pattern);
+                let value = build_value(quote!(Self::#ident), &variant.fields, &variant_parsers, &error);
+                branches.push(match variant.fields {
+                    Fields::Unit => quote! {
+                        if #regex.is_match(s) {
+                            return Ok(#value);
+                        }
+                    },
+                    _ => quote! {
+                        if let Some(caps) = #regex.captures(s) {
+                            return Ok(#value);
+                        }
+                    },
+                });
+                parsers.extend(variant_parsers);
+            }
+            quote! {
+                #(#branches)*
+                Err(#error::FormatError)
+            }
+        }
+        Data::Union(_) => return Err(Error::new(Span::call_site(), "FromRegex can't be derived for unions")),
+    };
+
+    let variants = parsers.iter().map(|FieldParser { variant, error_type, .. }| quote!(#variant(#error_type)));
+    let display = parsers.iter().map(|FieldParser { label, variant, .. }| {
+        quote!(#error::#variant(e) => write!(f, "Wrong {}: {}", #label, e))
+    });
+
+    Ok(quote! {
+        #[derive(Debug, Clone, PartialEq)]
+        #[allow(clippy::enum_variant_names)]
+        #vis enum #error {
+            FormatError,
+            #(#variants),*
+        }
+
+        impl ::std::fmt::Display for #error {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    #error::FormatError => write!(f, "Wrong line format"),
+                    #(#display),*
+                }
+            }
+        }
+
+        impl ::std::error::Error for #error {}
+
+        impl ::std::str::FromStr for #name {
+            type Err = #error;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                #body
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_pascal_case_test() {
+        assert_eq!(to_pascal_case("id"), "Id");
+        assert_eq!(to_pascal_case("date_time"), "DateTime");
+        assert_eq!(to_pascal_case("x"), "X");
+    }
+}
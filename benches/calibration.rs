@@ -0,0 +1,36 @@
+use aoc2018::{
+    calibration::{parallel_total, parse_changes, total},
+    random::Random,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+// About 8 MB of changes in the puzzle's format
+fn generated_input() -> String {
+    let mut random = Random::new(2018);
+    let mut input = String::new();
+    for _ in 0..1_000_000 {
+        let change = random.below(200_001) as i64 - 100_000;
+        input += &format!("{:+}\n", change);
+    }
+    input
+}
+
+fn calibration_sum(c: &mut Criterion) {
+    let input = generated_input();
+    let mut group = c.benchmark_group("calibration_sum");
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    group.sample_size(20);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| total::<i64>(&parse_changes(input.lines().map(String::from)).unwrap()).unwrap())
+    });
+    for threads in [1, 2, 4, 8] {
+        group.bench_with_input(BenchmarkId::new("parallel", threads), &threads, |b, &threads| {
+            b.iter(|| parallel_total::<i64>(input.as_bytes(), threads, 1 << 20).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, calibration_sum);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+use aoc2018::{
+    inventory::{checksum, Combine, Multiplicities},
+    random::Random,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+// IDs like the puzzle's: 26 lowercase letters from a smaller alphabet, so letters repeat
+fn generated_ids(count: usize) -> Vec<String> {
+    let mut random = Random::new(2018);
+    (0..count).map(|_| {
+        (0..26).map(|_| (b'a' + random.below(20) as u8) as char).collect()
+    }).collect()
+}
+
+fn letter_counting(c: &mut Criterion) {
+    let ids = generated_ids(100_000);
+    let mut group = c.benchmark_group("letter_counting");
+    group.throughput(Throughput::Elements(ids.len() as u64));
+
+    group.bench_function("general", |b| {
+        b.iter(|| ids.iter().filter(|id| {
+            let counts = Multiplicities::general(id);
+            counts.contains(2) && counts.contains(3)
+        }).count())
+    });
+    group.bench_function("ascii", |b| {
+        b.iter(|| ids.iter().filter(|id| {
+            let counts = Multiplicities::new(id);
+            counts.contains(2) && counts.contains(3)
+        }).count())
+    });
+    group.bench_function("checksum", |b| {
+        b.iter(|| checksum(ids.iter().cloned(), &[2, 3], Combine::Product).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, letter_counting);
+criterion_main!(benches);
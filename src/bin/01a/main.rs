@@ -1,12 +1,42 @@
+use aoc2018::calibration::{
+    parallel_total, parse_changes, total, CalibrationError, Frequency, Precision, DEFAULT_CHUNK_SIZE,
+};
+use num_bigint::BigInt;
+use std::fs::File;
+
+// `--threads N` sums the input in chunks on N threads, `--input PATH` reads another file
 fn main() {
-    let input = aoc2018::read_file("src/bin/01a/input.txt");
-    let result = calibrate(input);
-    println!("{}", result)
+    let precision = match aoc2018::flag_value("--precision").map_or(Ok(Precision::I32), |p| p.parse()) {
+        Ok(precision) => precision,
+        Err(error) => return println!("{}", error),
+    };
+    let path = aoc2018::flag_value("--input").unwrap_or_else(|| "src/bin/01a/input.txt".to_string());
+    let threads = match aoc2018::flag_value("--threads").map(|t| t.parse::<usize>()).transpose() {
+        Ok(threads) => threads,
+        Err(error) => return println!("Wrong number of threads: {}", error),
+    };
+    let result = match (threads, precision) {
+        (Some(threads), Precision::I32) => parallel::<i32>(&path, threads),
+        (Some(threads), Precision::I64) => parallel::<i64>(&path, threads),
+        (Some(threads), Precision::Big) => parallel::<BigInt>(&path, threads),
+        (None, Precision::I32) => calibrate::<i32>(aoc2018::read_file(&path)).map(|result| result.to_string()),
+        (None, Precision::I64) => calibrate::<i64>(aoc2018::read_file(&path)).map(|result| result.to_string()),
+        (None, Precision::Big) => calibrate::<BigInt>(aoc2018::read_file(&path)).map(|result| result.to_string()),
+    };
+    match result {
+        Ok(result) => println!("{}", result),
+        Err(error) => println!("{}", error),
+    }
 }
 
-fn calibrate(input: impl Iterator<Item = String>) -> i32 {
-    let numbers = input.map(|line| line.parse::<i32>().unwrap());
-    numbers.fold(0, |a, b| a + b)
+fn parallel<F: Frequency>(path: &str, threads: usize) -> Result<String, CalibrationError> {
+    let file = File::open(path).map_err(|error| CalibrationError::ReadError(error.to_string()))?;
+    parallel_total::<F>(file, threads, DEFAULT_CHUNK_SIZE).map(|result| result.to_string())
+}
+
+fn calibrate<F: Frequency>(input: impl Iterator<Item = String>) -> Result<F, CalibrationError> {
+    let changes = parse_changes(input)?;
+    total(&changes)
 }
 
 #[cfg(test)]
@@ -18,7 +48,18 @@ mod tests {
     #[test_case( "+1, +1, +1" => 3)]
     #[test_case( "+1, +1, -2" => 0)]
     #[test_case( "-1, -2, -3" => -6)]
+    #[test_case( "+1\n+1 # twice\n-2" => 0)]
     fn calibrate_tests(input: &str) -> i32 {
-        calibrate(input.split(", ").map(String::from))
+        calibrate(input.lines().map(String::from)).unwrap()
     }
-}
\ No newline at end of file
+
+    #[test_case( "+2147483647, +1" => "Line 1, column 14: frequency overflows after change 1".to_string(); "i32")]
+    fn calibrate_overflow_tests(input: &str) -> String {
+        calibrate::<i32>(input.lines().map(String::from)).unwrap_err().to_string()
+    }
+
+    #[test_case( "+2147483647, +1" => "2147483648".to_string(); "i64")]
+    fn calibrate_wide_tests(input: &str) -> String {
+        calibrate::<i64>(input.lines().map(String::from)).unwrap().to_string()
+    }
+}
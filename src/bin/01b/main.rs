@@ -1,32 +1,118 @@
-use std::collections::HashSet;
+use aoc2018::calibration::{
+    first_repeat, parse_changes, CalibrationError, Frequency, Precision, Repeat, TargetIndex, Trace, MAX_TRACE_STEPS,
+};
+use num_bigint::BigInt;
+use std::{error::Error, fs};
+
+struct Options {
+    verbose: bool,
+    stats: bool,
+    passes: Option<u64>,
+    csv: Option<String>,
+    svg: Option<String>,
+    targets: Option<String>,
+}
 
 fn main() {
+    let precision = match aoc2018::flag_value("--precision").map_or(Ok(Precision::I32), |p| p.parse()) {
+        Ok(precision) => precision,
+        Err(error) => return println!("{}", error),
+    };
+    let passes = match aoc2018::flag_value("--passes").map(|p| p.parse()).transpose() {
+        Ok(passes) => passes,
+        Err(error) => return println!("Wrong number of passes: {}", error),
+    };
+    let options = Options {
+        verbose: aoc2018::has_flag(&["-v", "--verbose"]),
+        stats: aoc2018::has_flag(&["--stats"]),
+        passes,
+        csv: aoc2018::flag_value("--csv"),
+        svg: aoc2018::flag_value("--svg"),
+        targets: aoc2018::flag_value("--target"),
+    };
     let input = aoc2018::read_file("src/bin/01b/input.txt");
-    let result = calibrate(input);
-    println!("{}", result)
+    let result = match precision {
+        Precision::I32 => run::<i32>(input, &options),
+        Precision::I64 => run::<i64>(input, &options),
+        Precision::Big => run::<BigInt>(input, &options),
+    };
+    if let Err(error) = result {
+        println!("{}", error);
+    }
 }
 
-fn calibrate(input: impl Iterator<Item=String>) -> i32 {
-    let numbers: Vec<_> = input.map(|line| line.parse::<i32>().unwrap()).collect();
-
-    let mut set = HashSet::new();
-    set.insert(0);
+fn run<F: Frequency>(input: impl Iterator<Item=String>, options: &Options) -> Result<(), Box<dyn Error>> {
+    let changes = parse_changes::<F>(input)?;
+    let repeat = first_repeat(&changes);
+    match &repeat {
+        Ok(repeat) => report(repeat, options.verbose),
+        Err(error) => println!("{}", error),
+    }
+    if let Some(targets) = &options.targets {
+        let index = TargetIndex::new(&changes)?;
+        for target in parse_changes::<F>(std::iter::once(targets.clone()))?.values() {
+            match index.first_step(target)? {
+                Some(0) => println!("{} is the initial frequency", target),
+                Some(step) => {
+                    let n = changes.len() as u64;
+                    let position = changes.position(((step - 1) % n) as usize);
+                    println!("{} is first reached at step {} (pass {}, line {}, column {})",
+                             target, step, (step - 1) / n + 1, position.line + 1, position.column + 1);
+                }
+                None => println!("{} is never reached", target),
+            }
+        }
+    }
+    if !options.stats && options.passes.is_none() && options.csv.is_none() && options.svg.is_none() {
+        return Ok(());
+    }
 
-    numbers.into_iter().cycle().try_fold((0, set), |(prev_sum, mut set), num| {
-        let next_sum = prev_sum + num;
+    // Without `--passes` the trace runs up to the repeat, or over a single pass if there is none
+    let steps = match (options.passes, &repeat) {
+        (Some(passes), _) => passes.checked_mul(changes.len() as u64)
+            .ok_or(CalibrationError::TraceTooLong { limit: MAX_TRACE_STEPS })?,
+        (None, Ok(repeat)) => repeat.step,
+        (None, Err(_)) => changes.len() as u64,
+    };
+    let trace = Trace::record(&changes, steps)?;
+    let stats = trace.stats();
+    println!("Frequencies reached over {} steps: {} to {}", steps, stats.min, stats.max);
+    let drifts: Vec<String> = stats.pass_drifts.iter().map(F::to_string).collect();
+    println!("Drift per pass: {}", drifts.join(", "));
+    if let Some(drift) = &stats.partial_drift {
+        println!("Drift over the last {} steps, short of a full pass: {}", steps % changes.len() as u64, drift);
+    }
+    println!("Frequencies visited more than once: {}", stats.repeated.len());
+    for (frequency, count) in &stats.repeated {
+        println!("  {}: {} times", frequency, count);
+    }
+    if let Some(path) = &options.csv {
+        fs::write(path, trace.to_csv())?;
+    }
+    if let Some(path) = &options.svg {
+        fs::write(path, trace.to_svg())?;
+    }
+    Ok(())
+}
 
-        if set.insert(next_sum) {
-            Result::Ok((next_sum, set))
-        } else {
-            Result::Err((next_sum, set))
-        }
-    }).unwrap_err().0
+fn report<F: Frequency>(repeat: &Repeat<F>, verbose: bool) {
+    println!("{}", repeat.frequency);
+    if verbose {
+        println!("First reached at step {}, reached again at step {} (pass {}, line {}, column {})",
+                 repeat.first_step, repeat.step, repeat.pass + 1, repeat.line + 1, repeat.column + 1);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use test_case::test_case;
     use super::*;
+    use aoc2018::calibration::CalibrationError::{self, EmptyInput, NeverRepeats};
+
+    fn calibrate<F: Frequency>(input: impl Iterator<Item=String>) -> Result<Repeat<F>, CalibrationError> {
+        let changes = parse_changes(input)?;
+        first_repeat(&changes)
+    }
 
     #[test_case("+1, -2, +3, +1" => 2)]
     #[test_case("+1, -1" => 0)]
@@ -34,6 +120,22 @@ mod tests {
     #[test_case("-6, +3, +8, +5, -6" => 5)]
     #[test_case("+7, +7, -2, -7, -4" => 14)]
     fn calibrate_tests(input: &str) -> i32 {
-        calibrate(input.split(", ").map(String::from))
+        calibrate(input.lines().map(String::from)).unwrap().frequency
     }
-}
\ No newline at end of file
+
+    #[test_case("+5, -3, +1" => Ok(5); "positive drift")]
+    #[test_case("-5, +3, -1" => Ok(-5); "negative drift")]
+    #[test_case("+4, -1, -3" => Ok(0); "zero drift")]
+    #[test_case("+1, +1" => Err(NeverRepeats { drift: "2".to_string() }); "never repeats")]
+    #[test_case("+1, +2" => Err(NeverRepeats { drift: "3".to_string() }); "distinct residues")]
+    #[test_case("-7" => Err(NeverRepeats { drift: "-7".to_string() }); "single change")]
+    #[test_case("0" => Ok(0); "no change")]
+    fn calibrate_repeat_tests(input: &str) -> Result<i32, CalibrationError> {
+        calibrate(input.lines().map(String::from)).map(|repeat| repeat.frequency)
+    }
+
+    #[test]
+    fn calibrate_empty_test() {
+        assert_eq!(calibrate::<i32>(std::iter::empty()), Err(EmptyInput));
+    }
+}
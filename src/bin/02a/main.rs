@@ -1,35 +1,40 @@
-use std::collections::HashMap;
+use aoc2018::inventory::{checksum_with_units, Combine, Normalization, Units};
 
+// `--multiplicities 2,3,4` and `--combine product|sum|table` change the checksum,
+// `--verbose` lists the IDs counted for each multiplicity and
+// `--graphemes` and `--normalize nfc|nfkc` count grapheme clusters of normalized IDs
 fn main() {
+    let multiplicities = aoc2018::flag_value("--multiplicities").unwrap_or_else(|| "2,3".to_string());
+    let multiplicities: Vec<usize> = match multiplicities.split(',').map(|k| k.trim().parse()).collect() {
+        Ok(multiplicities) => multiplicities,
+        Err(error) => return println!("Wrong multiplicities: {}", error),
+    };
+    let combine = match aoc2018::flag_value("--combine").map_or(Ok(Combine::Product), |c| c.parse()) {
+        Ok(combine) => combine,
+        Err(error) => return println!("{}", error),
+    };
+    let normalization = match aoc2018::flag_value("--normalize").map_or(Ok(Normalization::None), |n| n.parse()) {
+        Ok(normalization) => normalization,
+        Err(error) => return println!("{}", error),
+    };
+    let units = Units { graphemes: aoc2018::has_flag(&["--graphemes"]), normalization };
     let input = aoc2018::read_file("src/bin/02a/input.txt");
-    let result = find_checksum(input);
-    println!("{}", result)
-}
-
-fn find_checksum(input: impl Iterator<Item=String>) -> i32 {
-    let (times_2, times_3) = input.fold((0, 0), |acc, string| {
-        let mut increment_2: i32 = 0;
-        let mut increment_3: i32 = 0;
-
-        let result = string.chars().fold(HashMap::new(), |mut acc, chr| {
-            acc.entry(chr).and_modify(|i| *i += 1).or_insert(1);
-            acc
-        });
-
-        for (_, i) in result {
-            if i == 2 { increment_2 = 1 };
-            if i == 3 { increment_3 = 1 };
-            if increment_2 == 1 && increment_3 == 1 { break; };
+    let result = match checksum_with_units(input, &multiplicities, combine, units) {
+        Ok(result) => result,
+        Err(error) => return println!("{}", error),
+    };
+    println!("{}", result.value);
+    if aoc2018::has_flag(&["-v", "--verbose"]) {
+        for (k, ids) in &result.ids {
+            println!("{} IDs with a letter {} times: {}", ids.len(), k, ids.join(", "));
         }
-
-        (acc.0 + increment_2, acc.1 + increment_3)
-    });
-    times_2 * times_3
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc2018::inventory::{checksum, ChecksumValue};
 
     const INPUT: &str = "abcdef
 bababc
@@ -41,7 +46,7 @@ ababab";
 
     #[test]
     fn find_checksum_test() {
-        let result = find_checksum(INPUT.split("\n").map(String::from));
-        assert_eq!(result, 12)
+        let result = checksum(INPUT.split("\n").map(String::from), &[2, 3], Combine::Product);
+        assert_eq!(result.unwrap().value, ChecksumValue::Number(12))
     }
-}
\ No newline at end of file
+}
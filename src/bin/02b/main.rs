@@ -1,44 +1,86 @@
-use std::iter::FromIterator;
+use aoc2018::inventory::{
+    closest_pairs, clusters, find_pair_with_units, format_script, BkTree, Metric, Normalization, OneApart, PairError,
+    Units,
+};
 
+// `--within K` lists all pairs of IDs at most K letters apart,
+// `--near ID` the `--count N` (default 5) IDs closest to ID and
+// `--edit levenshtein|damerau` the closest pairs allowing dropped or extra letters.
+// `--clusters K` groups IDs linked by chains of IDs at most K letters apart.
+// `--graphemes` and `--normalize nfc|nfkc` compare grapheme clusters of normalized IDs in the pair search
 fn main() {
     let input = aoc2018::read_file("src/bin/02b/input.txt");
-    let result = find_correct_id(input);
-    println!("{}", result)
-}
-
-fn find_correct_id(input: impl Iterator<Item=String>) -> String {
-    let mut strings: Vec<Vec<char>> = Vec::new();
-    let input = input.map(|s| s.chars().collect::<Vec<_>>());
-
-    let mut diff_idx: Option<usize> = None;
-    let mut result: Option<Vec<char>> = None;
+    let within = aoc2018::flag_value("--within");
+    let near = aoc2018::flag_value("--near");
+    let edit = aoc2018::flag_value("--edit");
+    let cluster_distance = aoc2018::flag_value("--clusters");
+    if within.is_none() && near.is_none() && edit.is_none() && cluster_distance.is_none() {
+        let normalization = match aoc2018::flag_value("--normalize").map_or(Ok(Normalization::None), |n| n.parse()) {
+            Ok(normalization) => normalization,
+            Err(error) => return println!("{}", error),
+        };
+        let units = Units { graphemes: aoc2018::has_flag(&["--graphemes"]), normalization };
+        return match find_correct_id(input, units) {
+            Ok(result) => println!("{}", result.common),
+            Err(error) => println!("{}", error),
+        };
+    }
 
-    'outer: for input_line in input {
-        if !strings.is_empty() {
-            for string in &strings {
-                for (i, (a, b)) in input_line.iter().zip(string).enumerate() {
-                    if *a != *b {
-                        match diff_idx {
-                            None => diff_idx = Some(i),
-                            Some(_) => {
-                                diff_idx = None;
-                                break;
-                            }
-                        }
-                    }
-                }
-                if diff_idx.is_some() {
-                    result = Some(input_line);
-                    break 'outer;
-                }
-            }
+    let ids: Vec<String> = input.collect();
+    if let Some(metric) = edit {
+        let metric: Metric = match metric.parse() {
+            Ok(metric) => metric,
+            Err(error) => return println!("{}", error),
+        };
+        for pair in closest_pairs(&ids, metric) {
+            let (a, b) = pair.lines;
+            println!("Lines {} and {}: {} apart, {}", a + 1, b + 1, pair.distance, format_script(&pair.script));
+        }
+    }
+    if let Some(k) = cluster_distance {
+        let k = match k.parse() {
+            Ok(k) => k,
+            Err(error) => return println!("Wrong distance: {}", error),
+        };
+        let report = clusters(&ids, k);
+        println!("{} clusters, {} outliers", report.clusters.len(), report.outliers.len());
+        for cluster in &report.clusters {
+            let lines: Vec<String> = cluster.lines.iter().map(|line| (line + 1).to_string()).collect();
+            println!("{} IDs like {}: lines {}", cluster.lines.len(), cluster.pattern, lines.join(", "));
+        }
+        if !report.outliers.is_empty() {
+            let lines: Vec<String> = report.outliers.iter().map(|line| (line + 1).to_string()).collect();
+            println!("Outliers: lines {}", lines.join(", "));
         }
-        strings.push(input_line)
     }
+    // Only the searches below need the tree
+    if within.is_none() && near.is_none() {
+        return;
+    }
+    let tree = BkTree::new(&ids);
+    if let Some(k) = within {
+        let k = match k.parse() {
+            Ok(k) => k,
+            Err(error) => return println!("Wrong distance: {}", error),
+        };
+        for (a, b, distance) in tree.pairs_within(k) {
+            println!("Lines {} and {}: {} apart ({}, {})", a + 1, b + 1, distance, ids[a], ids[b]);
+        }
+    }
+    if let Some(id) = near {
+        let count = match aoc2018::flag_value("--count").map_or(Ok(5), |count| count.parse()) {
+            Ok(count) => count,
+            Err(error) => return println!("Wrong count: {}", error),
+        };
+        for (line, distance) in tree.nearest(&id, count) {
+            println!("Line {}: {} apart ({})", line + 1, distance, ids[line]);
+        }
+    }
+}
 
-    let mut result = result.unwrap();
-    result.remove(diff_idx.unwrap());
-    String::from_iter(result)
+fn find_correct_id(input: impl Iterator<Item=String>, units: Units) -> Result<OneApart, PairError> {
+    let ids: Vec<String> = input.collect();
+    find_pair_with_units(&ids, units)
 }
 
 #[cfg(test)]
@@ -55,7 +97,7 @@ wvxyz";
 
     #[test]
     fn find_correct_id_test() {
-        let result = find_correct_id(INPUT.split('\n').map(String::from));
-        assert_eq!(result, "fgij")
+        let result = find_correct_id(INPUT.split('\n').map(String::from), Units::default());
+        assert_eq!(result.unwrap().common, "fgij")
     }
 }
\ No newline at end of file
@@ -1,44 +1,137 @@
-mod claim;
+use aoc2018::{
+    fabric::{Claim, ClaimError, Fabric},
+    grid::{covered_area, Bounds},
+    interval::IntervalSet,
+};
+use std::{collections::HashMap, error::Error};
 
-use std::collections::HashSet;
-use claim::Claim;
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Method {
+    Sweep,
+    Fabric,
+    Pairs,
+}
 
+// `--threshold N` counts the squares under at least N claims instead of two.
+// `--fabric` counts them on a dense coverage grid instead of sweeping, and
+// `--pairs`, for two claims only, from the squares of every pairwise intersection
 fn main() {
+    let threshold = match aoc2018::flag_value("--threshold").map_or(Ok(2), |t| t.parse()) {
+        Ok(threshold) => threshold,
+        Err(error) => return println!("Wrong threshold: {}", error),
+    };
+    let method = if aoc2018::has_flag(&["--fabric"]) {
+        Method::Fabric
+    } else if aoc2018::has_flag(&["--pairs"]) {
+        Method::Pairs
+    } else {
+        Method::Sweep
+    };
     let input = aoc2018::read_file("src/bin/03a/input.txt");
-    let result = calculate(input);
-    println!("{}", result)
+    match calculate(input, method, threshold) {
+        Ok(result) => println!("{}", result),
+        Err(error) => println!("{}", error),
+    }
+}
+
+fn calculate(input: impl Iterator<Item=String>, method: Method, threshold: usize) -> Result<u128, Box<dyn Error>> {
+    let claims = make_claims(input)?;
+    match method {
+        Method::Sweep => Ok(covered_area(&claim_bounds(&claims), threshold)),
+        Method::Fabric => {
+            let fabric = Fabric::new(&claim_bounds(&claims))?;
+            Ok(fabric.overlap_area(threshold) as u128)
+        }
+        Method::Pairs if threshold != 2 => Err("--pairs only counts the squares under two or more claims".into()),
+        Method::Pairs => Ok(overlap_by_pairs(&claims) as u128),
+    }
 }
 
-fn calculate(input: impl Iterator<Item=String>) -> usize {
-    let claims = make_claims(input);
-    let mut intersecting_squares = HashSet::new();
+fn claim_bounds(claims: &[Claim]) -> Vec<Bounds> {
+    claims.iter().map(Claim::bounds).collect()
+}
+
+// The overlapping squares of each row, merged into x intervals
+fn overlap_by_pairs(claims: &[Claim]) -> usize {
+    let mut rows: HashMap<i64, IntervalSet> = HashMap::new();
     for i in 1..claims.len() {
         for j in 0..i {
             if let Some(intersection) = claims[i].intersection(&claims[j]) {
-                intersecting_squares.extend(intersection.squares());
+                let bounds = intersection.bounds();
+                for y in bounds.min.y..=bounds.max.y {
+                    rows.entry(y).or_default().insert(bounds.x_interval());
+                }
             }
         }
     }
-    intersecting_squares.len()
+    rows.values().map(|row| row.len() as usize).sum()
 }
 
-fn make_claims(input: impl Iterator<Item=String>) -> Vec<Claim> {
-    input.map(|s| Claim::new(&s)).collect()
+fn make_claims(input: impl Iterator<Item=String>) -> Result<Vec<Claim>, ClaimError> {
+    input.map(|s| s.parse()).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc2018::random::Random;
     use pretty_assertions::assert_eq;
+    use test_case::test_case;
 
     const INPUT: &str = "\
 #1 @ 1,3: 4x4
 #2 @ 3,1: 4x4
 #3 @ 5,5: 2x2";
 
+    #[test_case(Method::Sweep)]
+    #[test_case(Method::Fabric)]
+    #[test_case(Method::Pairs)]
+    fn calculate_test(method: Method) {
+        let result = calculate(INPUT.split('\n').map(String::from), method, 2).unwrap();
+        pretty_assertions::assert_eq!(result, 4)
+    }
+
+    #[test_case(Method::Sweep)]
+    #[test_case(Method::Fabric)]
+    fn calculate_threshold_test(method: Method) {
+        let results: Vec<u128> = (1..=3)
+            .map(|threshold| calculate(INPUT.lines().map(String::from), method, threshold).unwrap())
+            .collect();
+        pretty_assertions::assert_eq!(results, vec![32, 4, 0]);
+    }
+
+    #[test]
+    fn calculate_errors_test() {
+        let result = calculate(INPUT.lines().map(String::from), Method::Pairs, 3);
+        assert_eq!(result.unwrap_err().to_string(), "--pairs only counts the squares under two or more claims");
+        let result = calculate(std::iter::empty(), Method::Fabric, 2);
+        assert_eq!(result.unwrap_err().to_string(), "No claims to lay out on the fabric");
+        let result = calculate(INPUT.lines().map(String::from).chain(Some("#4 @ 1,1: 0x2".to_string())), Method::Sweep, 2);
+        assert_eq!(result.unwrap_err().to_string(), "Claim has no squares: '#4 @ 1,1: 0x2'");
+    }
+
+    #[test]
+    fn overlap_area_matches_pairs_test() {
+        let mut generator = Random::new(48);
+        let mut random = move |bound: u64| generator.below(bound);
+        for _ in 0..100 {
+            let input: Vec<String> = (1..=random(20))
+                .map(|id| format!("#{} @ {},{}: {}x{}", id, random(30), random(30), 1 + random(10), 1 + random(10)))
+                .collect();
+            let claims = make_claims(input.iter().cloned()).unwrap();
+            let expected = overlap_by_pairs(&claims);
+            assert_eq!(covered_area(&claim_bounds(&claims), 2), expected as u128, "{:?}", input);
+            if !claims.is_empty() {
+                let fabric = Fabric::new(&claim_bounds(&claims)).unwrap();
+                assert_eq!(fabric.overlap_area(2), expected as u64, "{:?}", input);
+            }
+        }
+    }
+
     #[test]
-    fn calculate_test() {
-        let result = calculate(INPUT.split('\n').map(String::from));
-        assert_eq!(result, 4)
+    fn overlap_area_large_test() {
+        let input = vec!["#1 @ 0,0: 4000000000x4000000000", "#2 @ 200000000,2000000000: 4000000000x2000000000"];
+        let result = calculate(input.into_iter().map(String::from), Method::Sweep, 2).unwrap();
+        assert_eq!(result, 3_800_000_000 * 2_000_000_000);
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,67 @@
-mod claim;
-
-use claim::Claim;
+use aoc2018::{
+    fabric::{Claim, ClaimError, Fabric, Overlaps},
+    grid::Bounds,
+};
+use std::error::Error;
 
+// `--all` lists every claim overlapping no other one and `--neighbours ID`
+// the claims overlapping claim ID. `--fabric` finds the claim on a dense coverage grid,
+// where its squares add up to its area only when no other claim covers any of them,
+// and `--pairs` checks every claim against every other one
 fn main() {
     let input = aoc2018::read_file("src/bin/03b/input.txt");
-    let result = calculate(input);
-    println!("{}", result)
+    let result = if let Some(id) = aoc2018::flag_value("--neighbours") {
+        print_neighbours(input, &id)
+    } else if aoc2018::has_flag(&["--all"]) {
+        make_claims(input).map_err(Box::from).map(|claims| {
+            for claim in alone(&claims) {
+                println!("{}", claim.id);
+            }
+        })
+    } else {
+        let result = if aoc2018::has_flag(&["--fabric"]) {
+            calculate_on_fabric(input)
+        } else if aoc2018::has_flag(&["--pairs"]) {
+            calculate_by_pairs(input)
+        } else {
+            calculate(input)
+        };
+        result.map(|id| println!("{}", id))
+    };
+    if let Err(error) = result {
+        println!("{}", error);
+    }
+}
+
+fn calculate(input: impl Iterator<Item=String>) -> Result<u32, Box<dyn Error>> {
+    let claims = make_claims(input)?;
+    let claim = alone(&claims).first().copied();
+    claim.map(|claim| claim.id).ok_or_else(|| "Every claim overlaps another one".into())
+}
+
+// Claims overlapping no other one, in input order
+fn alone(claims: &[Claim]) -> Vec<&Claim> {
+    let overlaps = Overlaps::new(&claim_bounds(claims));
+    overlaps.alone().map(|i| &claims[i]).collect()
+}
+
+fn print_neighbours(input: impl Iterator<Item=String>, id: &str) -> Result<(), Box<dyn Error>> {
+    let id: u32 = id.parse().map_err(|error| format!("Wrong claim ID: {}", error))?;
+    let claims = make_claims(input)?;
+    let index = claims.iter().position(|claim| claim.id == id).ok_or_else(|| format!("No claim #{}", id))?;
+    let overlaps = Overlaps::new(&claim_bounds(&claims));
+    for &neighbour in overlaps.neighbours(index) {
+        println!("{}", claims[neighbour].id);
+    }
+    Ok(())
+}
+
+fn claim_bounds(claims: &[Claim]) -> Vec<Bounds> {
+    claims.iter().map(Claim::bounds).collect()
 }
 
-fn calculate(input: impl Iterator<Item=String>) -> u32 {
-    let claims = make_claims(input);
+fn calculate_by_pairs(input: impl Iterator<Item=String>) -> Result<u32, Box<dyn Error>> {
+    let claims = make_claims(input)?;
     let mut result: Option<&Claim> = None;
     'outer: for i in 0..claims.len() {
         for j in 0..claims.len() {
@@ -19,11 +71,18 @@ fn calculate(input: impl Iterator<Item=String>) -> u32 {
         result = Some(&claims[i]);
         break;
     };
-    result.unwrap().id
+    result.map(|claim| claim.id).ok_or_else(|| "Every claim overlaps another one".into())
 }
 
-fn make_claims(input: impl Iterator<Item=String>) -> Vec<Claim> {
-    input.map(|s| Claim::new(&s)).collect()
+fn calculate_on_fabric(input: impl Iterator<Item=String>) -> Result<u32, Box<dyn Error>> {
+    let claims = make_claims(input)?;
+    let fabric = Fabric::new(&claim_bounds(&claims))?;
+    let claim = claims.iter().find(|claim| fabric.sum(&claim.bounds()) == claim.bounds().area());
+    claim.map(|claim| claim.id).ok_or_else(|| "Every claim overlaps another one".into())
+}
+
+fn make_claims(input: impl Iterator<Item=String>) -> Result<Vec<Claim>, ClaimError> {
+    input.map(|s| s.parse()).collect()
 }
 
 #[cfg(test)]
@@ -38,7 +97,33 @@ mod tests {
 
     #[test]
     fn calculate_test() {
-        let result = calculate(INPUT.split('\n').map(String::from));
-        assert_eq!(result, 3)
+        let result = calculate(INPUT.split('\n').map(String::from)).unwrap();
+        assert_eq!(result, 3);
+        let result = calculate(INPUT.lines().take(2).map(String::from));
+        assert_eq!(result.unwrap_err().to_string(), "Every claim overlaps another one");
+    }
+
+    #[test]
+    fn calculate_by_pairs_test() {
+        let result = calculate_by_pairs(INPUT.lines().map(String::from)).unwrap();
+        assert_eq!(result, 3);
+        let result = calculate_by_pairs(INPUT.lines().take(2).map(String::from));
+        assert_eq!(result.unwrap_err().to_string(), "Every claim overlaps another one");
+    }
+
+    #[test]
+    fn alone_test() {
+        let input = INPUT.lines().chain(vec!["#4 @ 0,0: 1x1", "#5 @ 6,1: 1x1"]);
+        let claims = make_claims(input.map(String::from)).unwrap();
+        let ids: Vec<u32> = alone(&claims).iter().map(|claim| claim.id).collect();
+        assert_eq!(ids, vec![3, 4]);
+    }
+
+    #[test]
+    fn calculate_on_fabric_test() {
+        let result = calculate_on_fabric(INPUT.lines().map(String::from)).unwrap();
+        assert_eq!(result, 3);
+        let result = calculate_on_fabric(INPUT.lines().take(2).map(String::from));
+        assert_eq!(result.unwrap_err().to_string(), "Every claim overlaps another one");
     }
-}
\ No newline at end of file
+}
@@ -1,64 +1,41 @@
+use aoc2018::FromRegex;
 use chrono::prelude::*;
-use regex::{Captures, Regex};
-use lazy_static::lazy_static;
 
-#[derive(Eq, PartialEq, Debug)]
+// [1518-11-01 00:00] Guard #10 begins shift
+// [1518-11-01 00:05] falls asleep
+// [1518-11-01 00:25] wakes up
+#[derive(Eq, PartialEq, Debug, FromRegex)]
+#[regex(r"(?x)^
+\[(?P<datetime>
+  \d{4}-\d{2}-\d{2}
+  \s
+  \d{2}:\d{2}
+)\]
+\s
+(?P<state>.+)$")]
 pub struct Event {
+    #[regex(with = "Event::build_time", error = "chrono::ParseError")]
     pub datetime: NaiveDateTime,
     pub state: State,
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, FromRegex)]
 pub enum State {
+    #[regex(r"^Guard #(\d+) begins shift$")]
     GuardShift(u32),
+    #[regex("^falls asleep$")]
     FallAsleep,
+    #[regex("^wakes up$")]
     WakeUp,
 }
 
-// [1518-11-01 00:00] Guard #10 begins shift
-// [1518-11-01 00:05] falls asleep
-// [1518-11-01 00:25] wakes up
 impl Event {
     pub fn new(s: &str) -> Self {
-        lazy_static! {
-            static ref EVENT_REGEX: Regex = Regex::new(
-r"(?xm)^
-\[(?P<time>
-  \d{4}-\d{2}-\d{2}
-  \s
-  \d{2}:\d{2}
-)\]
-\s
-(?:(?:Guard\s\#
-  (?P<id>\d+)
-\ begins\ shift$)
-|
-(?P<sleep>falls\ asleep$)
-|
-(?P<wake>wakes\ up$))"
-            ).unwrap();
-        }
-        let caps = EVENT_REGEX.captures(s).unwrap();
-        let time = Self::build_time(&caps["time"]);
-        let content = Self::build_content(caps);
-        Self { datetime: time, state: content }
+        s.parse().unwrap()
     }
 
-    fn build_time(s: &str) -> NaiveDateTime {
-        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
-    }
-
-
-    fn build_content(caps: Captures) -> State {
-        if let Some(id) = caps.name("id") {
-            State::GuardShift(id.as_str().parse().unwrap())
-        } else if let Some(_) = caps.name("sleep") {
-            State::FallAsleep
-        } else if let Some(_) = caps.name("wake") {
-            State::WakeUp
-        } else {
-            panic!("Wrong string format!")
-        }
+    fn build_time(s: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
     }
 }
 
@@ -103,4 +80,17 @@ mod tests {
         };
         assert_eq!(result, expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn from_str_incorrect_datetime_test() {
+        let result = "[1518-13-01 00:25] wakes up".parse::<Event>().unwrap_err();
+        assert!(matches!(result, EventError::DatetimeError(_)));
+    }
+
+    #[test]
+    fn from_str_incorrect_state_test() {
+        let result = "[1518-11-01 00:25] dozes off".parse::<Event>().unwrap_err();
+        assert_eq!(result, EventError::StateError(StateError::FormatError));
+        assert_eq!(result.to_string(), "Wrong state: Wrong line format");
+    }
+}
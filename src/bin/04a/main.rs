@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::ops::Range;
+use aoc2018::interval::{coverage, Interval};
 use chrono::{Duration, NaiveDateTime, Timelike};
 use event::Event;
 use event::State;
@@ -20,19 +20,20 @@ fn calculate(input: impl Iterator<Item=String>) -> u32 {
     let guards = build_guards(events);
 
     let sleepy_guard_index = guards.iter().max_by_key(|(_, v)| {
-        v.iter().map(|e| e.end - e.start + 1).sum::<u32>()
+        v.iter().map(|e| e.end - e.start + 1).sum::<i64>()
     }).unwrap().0;
     let sleepy_guard = &guards[sleepy_guard_index];
 
-    let sleepy_minute = (0u32..60).map(|i| {
-        sleepy_guard.iter().filter(|e| e.contains(&i)).count()
-    }).enumerate().max_by_key(|e| e.1).unwrap().0 as u32;
+    // The last of the minutes asleep most often, like counting them one by one would pick
+    let (minutes, _) = coverage(sleepy_guard.iter().copied()).into_iter()
+        .max_by_key(|(_, count)| *count).unwrap();
+    let sleepy_minute = (minutes.end - 1) as u32;
 
     sleepy_guard_index * sleepy_minute
 }
 
-fn build_guards(events: Vec<Event>) -> HashMap<u32, Vec<Range<u32>>> {
-    let mut guards: HashMap<u32, Vec<Range<u32>>> = HashMap::new();
+fn build_guards(events: Vec<Event>) -> HashMap<u32, Vec<Interval>> {
+    let mut guards: HashMap<u32, Vec<Interval>> = HashMap::new();
     let mut current_guard: Option<u32> = None;
     let mut asleep_event: Option<Event> = None;
 
@@ -49,8 +50,8 @@ fn build_guards(events: Vec<Event>) -> HashMap<u32, Vec<Range<u32>>> {
                 Some(unwrapped_event) => {
                     if event.state == State::WakeUp {
                         let (sleep_start, sleep_end) = build_sleep_bounds(event, &unwrapped_event);
-                        guards.get_mut(&guard).unwrap().push(
-                            sleep_start.minute()..sleep_end.minute());
+                        guards.get_mut(&guard).unwrap().push(Interval::new(
+                            sleep_start.minute() as i64, sleep_end.minute() as i64));
                         asleep_event = None
                     }
                 }
@@ -106,4 +107,18 @@ mod tests {
         let result = calculate(INPUT.split('\n').map(String::from));
         assert_eq!(result, 240)
     }
+
+    // Guard #10 sleeps as long at minutes 5 to 9 as at 20 to 24, and the last of them wins
+    const TIE_INPUT: &str = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:10] wakes up
+[1518-11-01 00:20] falls asleep
+[1518-11-01 00:25] wakes up";
+
+    #[test]
+    fn calculate_tie_test() {
+        let result = calculate(TIE_INPUT.lines().map(String::from));
+        assert_eq!(result, 240)
+    }
 }
\ No newline at end of file
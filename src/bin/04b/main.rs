@@ -1,6 +1,5 @@
 use std::collections::HashMap;
-use std::ops::Range;
-use arrayvec::ArrayVec;
+use aoc2018::interval::{coverage, Interval};
 use chrono::{Duration, NaiveDateTime, Timelike};
 use event::Event;
 use event::State;
@@ -17,18 +16,12 @@ fn main() {
 fn calculate(input: impl Iterator<Item=String>) -> u32 {
     let mut events: Vec<Event> = input.map(|s| Event::new(&s)).collect();
     events.sort_unstable_by_key(|e| e.datetime);
-    let guards: HashMap<u32, Vec<Range<u32>>> = build_guards(events);
+    let guards: HashMap<u32, Vec<Interval>> = build_guards(events);
 
-    let guards_by_minutes = guards.iter().map(|(id, timeline)| {
-        let result: ArrayVec<u32, 60> = (0u32..60).map(|i| {
-            timeline.iter().filter(|e| e.contains(&i)).count() as u32
-        }).collect();
-        (*id, result)
-    });
-
-    let guards_max_minutes = guards_by_minutes.map(|(id, minutes)| {
-        let (minute, amount) = minutes.iter().enumerate().max_by_key(|(_, v)| *v).unwrap();
-        (id, minute, *amount)
+    let guards_max_minutes = guards.iter().filter_map(|(id, timeline)| {
+        let (minutes, amount) = coverage(timeline.iter().copied()).into_iter()
+            .max_by_key(|(_, count)| *count)?;
+        Some((*id, minutes.end - 1, amount))
     });
 
     let (sleepy_guard_id, sleepy_minute, _) = guards_max_minutes.max_by_key(|(_, _, amount)| *amount).unwrap();
@@ -36,8 +29,8 @@ fn calculate(input: impl Iterator<Item=String>) -> u32 {
     sleepy_guard_id * sleepy_minute as u32
 }
 
-fn build_guards(events: Vec<Event>) -> HashMap<u32, Vec<Range<u32>>> {
-    let mut guards: HashMap<u32, Vec<Range<u32>>> = HashMap::new();
+fn build_guards(events: Vec<Event>) -> HashMap<u32, Vec<Interval>> {
+    let mut guards: HashMap<u32, Vec<Interval>> = HashMap::new();
     let mut current_guard: Option<u32> = None;
     let mut asleep_event: Option<Event> = None;
 
@@ -54,8 +47,8 @@ fn build_guards(events: Vec<Event>) -> HashMap<u32, Vec<Range<u32>>> {
                 Some(unwrapped_event) => {
                     if event.state == State::WakeUp {
                         let (sleep_start, sleep_end) = build_sleep_bounds(event, &unwrapped_event);
-                        guards.get_mut(&guard).unwrap().push(
-                            sleep_start.minute()..sleep_end.minute());
+                        guards.get_mut(&guard).unwrap().push(Interval::new(
+                            sleep_start.minute() as i64, sleep_end.minute() as i64));
                         asleep_event = None
                     }
                 }
@@ -111,4 +104,18 @@ mod tests {
         let result = calculate(INPUT.split('\n').map(String::from));
         assert_eq!(result, 4455)
     }
+
+    // Guard #10 sleeps as long at minutes 5 to 9 as at 20 to 24, and the last of them wins
+    const TIE_INPUT: &str = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:10] wakes up
+[1518-11-01 00:20] falls asleep
+[1518-11-01 00:25] wakes up";
+
+    #[test]
+    fn calculate_tie_test() {
+        let result = calculate(TIE_INPUT.lines().map(String::from));
+        assert_eq!(result, 240)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,473 @@
+mod frequency;
+mod stream;
+mod target;
+mod trace;
+
+use std::{
+    collections::HashMap,
+    error,
+    fmt::{Display, Formatter},
+};
+use CalibrationError::{EmptyInput, InvalidChange, NeverRepeats, OutOfRange, Overflow, ReadError, TooManySteps, TraceTooLong, Unbounded};
+
+pub use frequency::{Frequency, Precision, PrecisionError};
+pub use stream::{parallel_total, DEFAULT_CHUNK_SIZE};
+pub use target::TargetIndex;
+pub use trace::{Trace, TracePoint, TraceStats, MAX_TRACE_STEPS};
+
+// Where a change starts in the input, counted from 0
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+// The changes in the order they are applied, each with its position
+#[derive(Debug, Clone, PartialEq)]
+pub struct Changes<F> {
+    values: Vec<F>,
+    positions: Vec<Position>,
+}
+
+impl<F> Changes<F> {
+    pub fn values(&self) -> &[F] {
+        &self.values
+    }
+
+    pub fn position(&self, index: usize) -> Position {
+        self.positions[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+// One change per line, like the puzzle input
+impl<F> From<Vec<F>> for Changes<F> {
+    fn from(values: Vec<F>) -> Self {
+        let positions = (0..values.len()).map(|line| Position { line, column: 0 }).collect();
+        Self { values, positions }
+    }
+}
+
+// Steps count the applied changes, so step 0 is the initial frequency of 0
+// and step `s` is produced by change `(s - 1) % n` during pass `(s - 1) / n`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repeat<F> {
+    pub frequency: F,
+    pub first_step: u64,
+    pub step: u64,
+    pub pass: u64,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<F> Repeat<F> {
+    fn new<C>(frequency: F, first_step: u64, step: u64, changes: &Changes<C>) -> Self {
+        let n = changes.len() as u64;
+        let Position { line, column } = changes.position(((step - 1) % n) as usize);
+        Self { frequency, first_step, step, pass: (step - 1) / n, line, column }
+    }
+}
+
+// Changes may be separated by newlines, commas or whitespace, positive changes may leave
+// out their sign, a sign may be followed by spaces and anything after `#` or `//` is a comment
+pub fn parse_changes<F: Frequency>(lines: impl Iterator<Item=String>) -> Result<Changes<F>, CalibrationError> {
+    let mut values = Vec::new();
+    let mut positions = Vec::new();
+    for (line, text) in lines.enumerate() {
+        for (column, token) in tokens(&text) {
+            values.push(parse_change(line, column, token)?);
+            positions.push(Position { line, column });
+        }
+    }
+    Ok(Changes { values, positions })
+}
+
+// Splits a line into `(column, token)` pairs, columns counted in characters
+fn tokens(text: &str) -> Vec<(usize, &str)> {
+    let end = text.find('#').into_iter().chain(text.find("//")).min().unwrap_or(text.len());
+    let text = &text[..end];
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let is_separator = |c: char| c == ',' || c.is_whitespace();
+
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if is_separator(chars[i].1) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        if matches!(chars[i].1, '+' | '-') {
+            i += 1;
+            let digits = chars[i..].iter().position(|&(_, c)| c == ',' || !c.is_whitespace());
+            if let Some(offset) = digits.filter(|&offset| chars[i + offset].1.is_ascii_digit()) {
+                i += offset;
+            }
+        }
+        while i < chars.len() && !is_separator(chars[i].1) {
+            i += 1;
+        }
+        let to = chars.get(i).map_or(text.len(), |&(index, _)| index);
+        result.push((start, &text[chars[start].0..to]));
+    }
+    result
+}
+
+fn parse_change<F: Frequency>(line: usize, column: usize, token: &str) -> Result<F, CalibrationError> {
+    let change: String = token.chars().filter(|c| !c.is_whitespace()).collect();
+    let digits = change.strip_prefix(['+', '-']).unwrap_or(&change);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(InvalidChange { line, column, text: token.to_string() });
+    }
+    change.parse().map_err(|_| OutOfRange { line, column, text: token.to_string() })
+}
+
+pub fn total<F: Frequency>(changes: &Changes<F>) -> Result<F, CalibrationError> {
+    changes.values().iter().enumerate().try_fold(F::zero(), |sum, (index, change)| {
+        sum.checked_add(change).ok_or_else(|| overflow(changes, index))
+    })
+}
+
+// Step `k * n + i` is at frequency `sums[i] + k * drift`, where `sums` are the frequencies
+// of the first pass. So frequency `sums[j]` comes back at step `k * n + i` whenever
+// `sums[i] + k * drift == sums[j]`: only sums with the same residue modulo the drift
+// can meet, and the nearest one in the direction of the drift needs the fewest passes.
+pub fn first_repeat<F: Frequency>(changes: &Changes<F>) -> Result<Repeat<F>, CalibrationError> {
+    let n = changes.len();
+    if n == 0 {
+        return Err(EmptyInput);
+    }
+
+    let mut sums = Vec::with_capacity(n);
+    let mut seen = HashMap::new();
+    let mut sum = F::zero();
+    for (step, change) in changes.values().iter().enumerate() {
+        if let Some(&first_step) = seen.get(&sum) {
+            return Ok(Repeat::new(sum, first_step, step as u64, changes));
+        }
+        seen.insert(sum.clone(), step as u64);
+        sums.push(sum.clone());
+        sum = sum.checked_add(change).ok_or_else(|| overflow(changes, step))?;
+    }
+    let drift = sum;
+    if drift == F::zero() {
+        return Ok(Repeat::new(F::zero(), 0, n as u64, changes));
+    }
+
+    let modulus = drift.abs();
+    let mut groups: HashMap<F, Vec<usize>> = HashMap::new();
+    for (i, sum) in sums.iter().enumerate() {
+        groups.entry(sum.rem_euclid(&modulus)).or_default().push(i);
+    }
+
+    let mut best: Option<(u64, usize)> = None;
+    for group in groups.values_mut() {
+        if drift.is_negative() {
+            group.sort_unstable_by(|&a, &b| sums[b].cmp(&sums[a]));
+        } else {
+            group.sort_unstable_by(|&a, &b| sums[a].cmp(&sums[b]));
+        }
+        for pair in group.windows(2) {
+            let (i, j) = (pair[0], pair[1]);
+            let step = sums[j].checked_sub(&sums[i])
+                .and_then(|distance| distance.checked_div(&drift))
+                .and_then(|passes| passes.to_u64())
+                .and_then(|passes| passes.checked_mul(n as u64))
+                .and_then(|step| step.checked_add(i as u64))
+                .ok_or(TooManySteps)?;
+            if best.is_none_or(|(best_step, _)| step < best_step) {
+                best = Some((step, j));
+            }
+        }
+    }
+    let (step, j) = best.ok_or_else(|| NeverRepeats { drift: drift.to_string() })?;
+    check_skipped_passes(changes, &sums, &drift, step)?;
+    Ok(Repeat::new(sums[j].clone(), j as u64, step, changes))
+}
+
+// The frequencies between the first pass and the repeat are never computed,
+// so check that none of them up to `last_step` would leave the range of `F`
+fn check_skipped_passes<F: Frequency>(changes: &Changes<F>, sums: &[F], drift: &F, last_step: u64) -> Result<(), CalibrationError> {
+    match first_skipped_overflow(changes, sums, drift) {
+        Some((step, error)) if step <= last_step as i128 => Err(error),
+        _ => Ok(()),
+    }
+}
+
+// The first step, if any, at which a nonzero `drift` takes the frequency out of the range of `F`
+fn first_skipped_overflow<F: Frequency>(changes: &Changes<F>, sums: &[F], drift: &F) -> Option<(i128, CalibrationError)> {
+    let (min, max) = F::limits()?;
+    let n = sums.len() as i128;
+    let drift = drift.to_i128().unwrap();
+    let step = sums.iter().enumerate().map(|(i, sum)| {
+        let sum = sum.to_i128().unwrap();
+        let headroom = if drift > 0 { max - sum } else { sum - min };
+        (headroom / drift.abs() + 1) * n + i as i128
+    }).min()?;
+    Some((step, overflow(changes, ((step - 1) % n) as usize)))
+}
+
+fn overflow<F: Frequency>(changes: &Changes<F>, index: usize) -> CalibrationError {
+    let Position { line, column } = changes.position(index);
+    Overflow { line, column, change: changes.values()[index].to_string() }
+}
+
+// Lines and columns are counted from 0 like everywhere else, but shown counted from 1
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalibrationError {
+    EmptyInput,
+    InvalidChange { line: usize, column: usize, text: String },
+    OutOfRange { line: usize, column: usize, text: String },
+    Overflow { line: usize, column: usize, change: String },
+    NeverRepeats { drift: String },
+    TooManySteps,
+    TraceTooLong { limit: u64 },
+    Unbounded,
+    ReadError(String),
+}
+
+impl Display for CalibrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmptyInput => { write!(f, "No frequency changes given") }
+            InvalidChange { line, column, text } => {
+                write!(f, "Line {}, column {}: '{}' is not a frequency change", line + 1, column + 1, text)
+            }
+            OutOfRange { line, column, text } => {
+                write!(f, "Line {}, column {}: change '{}' does not fit the precision", line + 1, column + 1, text)
+            }
+            Overflow { line, column, change } => {
+                write!(f, "Line {}, column {}: frequency overflows after change {}", line + 1, column + 1, change)
+            }
+            NeverRepeats { drift } => {
+                write!(f, "Frequency drifts by {} every pass and never repeats", drift)
+            }
+            TooManySteps => { write!(f, "Frequency repeats after more steps than can be counted") }
+            TraceTooLong { limit } => { write!(f, "Traces are limited to {} steps", limit) }
+            Unbounded => { write!(f, "Summing on threads needs i32 or i64 precision") }
+            ReadError(error) => { write!(f, "Could not read the changes: {}", error) }
+        }
+    }
+}
+
+impl error::Error for CalibrationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cycle::find_repeat;
+    use crate::random::Random;
+    use num_bigint::BigInt;
+    use std::collections::HashSet;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    // Kept as the reference for `first_repeat`: walks the passes and finds the first frequency seen twice
+    fn first_repeat_by_simulation<F: Frequency>(changes: &Changes<F>) -> Result<Repeat<F>, CalibrationError> {
+        let n = changes.len();
+        if n == 0 {
+            return Err(EmptyInput);
+        }
+        let drift = total(changes)?;
+        if !can_repeat(changes.values(), &drift) {
+            return Err(NeverRepeats { drift: drift.to_string() });
+        }
+
+        // The sums only run out on an overflow, as the frequencies are known to repeat
+        let mut overflowed = None;
+        let sums = changes.values().iter().enumerate().cycle().scan(F::zero(), |sum, (index, change)| {
+            match sum.checked_add(change) {
+                Some(next) => {
+                    *sum = next;
+                    Some(sum.clone())
+                }
+                None => {
+                    overflowed = Some(overflow(changes, index));
+                    None
+                }
+            }
+        });
+        match find_repeat(std::iter::once(F::zero()).chain(sums)) {
+            Some(cycle) => {
+                let step = cycle.start + cycle.period;
+                Ok(Repeat::new(cycle.state, cycle.start as u64, step as u64, changes))
+            }
+            None => Err(overflowed.unwrap()),
+        }
+    }
+
+    // After `k` passes every frequency of the first pass is shifted by `k * drift`,
+    // so a frequency can only come back if two of them are equal modulo the drift
+    fn can_repeat<F: Frequency>(changes: &[F], drift: &F) -> bool {
+        if *drift == F::zero() {
+            return true;
+        }
+        let modulus = drift.abs();
+        let mut residues = HashSet::new();
+        let mut sum = F::zero();
+        changes.iter().any(|change| {
+            let repeated = !residues.insert(sum.rem_euclid(&modulus));
+            sum = sum.checked_add(change).unwrap();
+            repeated
+        })
+    }
+
+    fn lines(input: &str) -> impl Iterator<Item=String> + '_ {
+        input.lines().map(String::from)
+    }
+
+    fn changes<F: Clone>(values: &[F]) -> Changes<F> {
+        values.to_vec().into()
+    }
+
+    #[test]
+    fn first_repeat_in_first_pass_test() {
+        // 0 -> 3 -> 1 -> 4 -> 1
+        let result = first_repeat(&changes(&[3, -2, 3, -3, 5])).unwrap();
+        let expected = Repeat { frequency: 1, first_step: 2, step: 4, pass: 0, line: 3, column: 0 };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn first_repeat_after_passes_test() {
+        // 0 -> 3 -> 6 -> 10 -> 8 -> 4 | -> 7 -> 10
+        let result = first_repeat(&changes(&[3, 3, 4, -2, -4])).unwrap();
+        let expected = Repeat { frequency: 10, first_step: 3, step: 7, pass: 1, line: 1, column: 0 };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn first_repeat_zero_drift_test() {
+        let result = first_repeat(&changes(&[1, -1])).unwrap();
+        let expected = Repeat { frequency: 0, first_step: 0, step: 2, pass: 0, line: 1, column: 0 };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn first_repeat_at_end_of_pass_test() {
+        // 0 -> 2 -> 1 | -> 3 -> 2
+        let result = first_repeat(&changes(&[2, -1])).unwrap();
+        let expected = Repeat { frequency: 2, first_step: 1, step: 4, pass: 1, line: 1, column: 0 };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn first_repeat_matches_simulation_test() {
+        let mut generator = Random::new(2018);
+        let mut random = move |bound: u64| generator.below(bound);
+        for _ in 0..2000 {
+            let length = 1 + random(12) as usize;
+            let changes: Changes<i32> = (0..length).map(|_| random(21) as i32 - 10).collect::<Vec<_>>().into();
+            assert_eq!(first_repeat(&changes), first_repeat_by_simulation(&changes), "{:?}", changes);
+        }
+    }
+
+    #[test]
+    fn first_repeat_matches_simulation_on_input_test() {
+        let changes: Changes<i32> = parse_changes(crate::read_file("src/bin/01b/input.txt")).unwrap();
+        assert_eq!(first_repeat(&changes), first_repeat_by_simulation(&changes));
+    }
+
+    #[test]
+    fn parse_changes_test() {
+        let result = parse_changes::<i32>(lines("+1, -2, 3000000000, x1"));
+        assert_eq!(result, Err(OutOfRange { line: 0, column: 8, text: "3000000000".to_string() }));
+
+        let result = parse_changes::<i64>(lines("+1, -2, 3000000000, x1"));
+        assert_eq!(result, Err(InvalidChange { line: 0, column: 20, text: "x1".to_string() }));
+
+        let result = parse_changes::<BigInt>(lines("+1, -2, +30000000000000000000000"));
+        assert_eq!(result.unwrap().values()[2], "30000000000000000000000".parse::<BigInt>().unwrap());
+    }
+
+    #[test_case("+1\n-2\n+3\n+1" ; "newlines")]
+    #[test_case("+1, -2, +3, +1" ; "commas")]
+    #[test_case("+1 -2\t+3   +1" ; "whitespace")]
+    #[test_case("1, -2, 3, 1" ; "implicit signs")]
+    #[test_case("+ 1, -  2,+3,,+1" ; "spaced signs")]
+    #[test_case("# drift\n+1, -2 // two\n\n  +3\n+1 # last" ; "comments and blank lines")]
+    fn parse_changes_format_test(input: &str) {
+        pretty_assertions::assert_eq!(parse_changes::<i32>(lines(input)).map(|changes| changes.values().to_vec()), Ok(vec![1, -2, 3, 1]));
+    }
+
+    #[test_case("+1\n+x", InvalidChange { line: 1, column: 0, text: "+x".to_string() })]
+    #[test_case("+1, 5a, 3", InvalidChange { line: 0, column: 4, text: "5a".to_string() })]
+    #[test_case("+1, + , 3", InvalidChange { line: 0, column: 4, text: "+".to_string() })]
+    #[test_case("+1 +-3", InvalidChange { line: 0, column: 3, text: "+-3".to_string() })]
+    #[test_case("\n  ++ 3", InvalidChange { line: 1, column: 2, text: "++".to_string() })]
+    #[test_case("+1\n-  3000000000", OutOfRange { line: 1, column: 0, text: "-  3000000000".to_string() })]
+    fn parse_changes_error_test(input: &str, expected: CalibrationError) {
+        pretty_assertions::assert_eq!(parse_changes::<i32>(lines(input)), Err(expected));
+    }
+
+    #[test]
+    fn parse_changes_error_display_test() {
+        let error = parse_changes::<i32>(lines("+1\n+2, ?3")).unwrap_err();
+        assert_eq!(error.to_string(), "Line 2, column 5: '?3' is not a frequency change");
+    }
+
+    #[test]
+    fn total_overflow_test() {
+        let result = total(&changes(&[1, i32::MAX, -5]));
+        assert_eq!(result, Err(Overflow { line: 1, column: 0, change: i32::MAX.to_string() }));
+        assert_eq!(total(&changes(&[1, i32::MAX as i64, -5])), Ok(i32::MAX as i64 - 4));
+    }
+
+    #[test]
+    fn positions_test() {
+        let changes = parse_changes::<i32>(lines("# header\n+1,  -2\n\n  + 3")).unwrap();
+        let positions: Vec<Position> = (0..changes.len()).map(|index| changes.position(index)).collect();
+        let expected = vec![Position { line: 1, column: 0 }, Position { line: 1, column: 5 }, Position { line: 3, column: 2 }];
+        assert_eq!(positions, expected);
+
+        let error = total(&parse_changes::<i32>(lines("+1, +2147483647")).unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), "Line 1, column 5: frequency overflows after change 2147483647");
+
+        // 0 -> 3 -> 1 | -> 4 -> 2 | -> 5 -> 3
+        let repeat = first_repeat(&parse_changes::<i32>(lines("+3, -2")).unwrap()).unwrap();
+        assert_eq!((repeat.step, repeat.line, repeat.column), (6, 0, 4));
+        // 0 -> 3 -> 1 -> 3
+        let repeat = first_repeat(&parse_changes::<i32>(lines("+3 -2 +2 -4")).unwrap()).unwrap();
+        assert_eq!((repeat.step, repeat.line, repeat.column), (3, 0, 6));
+    }
+
+    #[test]
+    fn total_big_test() {
+        let changes = parse_changes::<BigInt>(lines("+9223372036854775807, +9223372036854775807, +2")).unwrap();
+        assert_eq!(total(&changes).unwrap().to_string(), "18446744073709551616");
+    }
+
+    #[test]
+    fn first_repeat_overflow_in_first_pass_test() {
+        let result = first_repeat(&changes(&[5, i32::MAX, -3]));
+        assert_eq!(result, Err(Overflow { line: 1, column: 0, change: i32::MAX.to_string() }));
+    }
+
+    // 0 -> 2000000000 -> 1 | -> 2000000001 -> 2 | ... the frequency climbs past
+    // `i32::MAX` long before 2000000000 comes back
+    #[test]
+    fn first_repeat_overflow_in_skipped_passes_test() {
+        let result = first_repeat(&changes(&[2_000_000_000, -1_999_999_999]));
+        assert_eq!(result, Err(Overflow { line: 0, column: 0, change: "2000000000".to_string() }));
+
+        let result = first_repeat(&changes(&[2_000_000_000i64, -1_999_999_999])).unwrap();
+        assert_eq!(result.frequency, 2_000_000_000);
+        assert_eq!(result.step, 4_000_000_000);
+
+        let big: Changes<BigInt> = vec![2_000_000_000.into(), (-1_999_999_999).into()].into();
+        assert_eq!(first_repeat(&big).unwrap().step, 4_000_000_000);
+    }
+
+    #[test]
+    fn first_repeat_overflow_matches_simulation_test() {
+        let changes = changes(&[i32::MAX - 10, -(i32::MAX - 13)]);
+        assert_eq!(first_repeat(&changes), first_repeat_by_simulation(&changes));
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    convert::TryFrom,
+    error,
+    fmt::{Debug, Display, Formatter},
+    hash::Hash,
+    str::FromStr,
+};
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+// Integer type the frequency is computed in. Fixed-width types report their
+// `limits` so that the frequencies a solver skips over can still be checked for overflow
+pub trait Frequency: Clone + Eq + Ord + Hash + Debug + Display + FromStr {
+    fn zero() -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_div(&self, other: &Self) -> Option<Self>;
+    fn rem_euclid(&self, modulus: &Self) -> Self;
+    fn abs(&self) -> Self;
+    fn is_negative(&self) -> bool;
+    fn to_i128(&self) -> Option<i128>;
+    fn from_i128(value: i128) -> Option<Self>;
+    fn to_u64(&self) -> Option<u64>;
+    fn limits() -> Option<(i128, i128)>;
+}
+
+macro_rules! fixed_width_frequency {
+    ($($t:ty),*) => {$(
+        impl Frequency for $t {
+            fn zero() -> Self { 0 }
+            fn checked_add(&self, other: &Self) -> Option<Self> { <$t>::checked_add(*self, *other) }
+            fn checked_sub(&self, other: &Self) -> Option<Self> { <$t>::checked_sub(*self, *other) }
+            fn checked_div(&self, other: &Self) -> Option<Self> { <$t>::checked_div(*self, *other) }
+            fn rem_euclid(&self, modulus: &Self) -> Self { <$t>::rem_euclid(*self, *modulus) }
+            fn abs(&self) -> Self { <$t>::abs(*self) }
+            fn is_negative(&self) -> bool { *self < 0 }
+            fn to_i128(&self) -> Option<i128> { Some(*self as i128) }
+            fn from_i128(value: i128) -> Option<Self> { <$t>::try_from(value).ok() }
+            fn to_u64(&self) -> Option<u64> { ToPrimitive::to_u64(self) }
+            fn limits() -> Option<(i128, i128)> { Some((<$t>::MIN as i128, <$t>::MAX as i128)) }
+        }
+    )*};
+}
+
+fixed_width_frequency!(i32, i64);
+
+impl Frequency for BigInt {
+    fn zero() -> Self { Zero::zero() }
+    fn checked_add(&self, other: &Self) -> Option<Self> { Some(self + other) }
+    fn checked_sub(&self, other: &Self) -> Option<Self> { Some(self - other) }
+    fn checked_div(&self, other: &Self) -> Option<Self> {
+        if other.is_zero() { None } else { Some(self / other) }
+    }
+    fn rem_euclid(&self, modulus: &Self) -> Self { ((self % modulus) + modulus) % modulus }
+    fn abs(&self) -> Self { Signed::abs(self) }
+    fn is_negative(&self) -> bool { Signed::is_negative(self) }
+    fn to_i128(&self) -> Option<i128> { ToPrimitive::to_i128(self) }
+    fn from_i128(value: i128) -> Option<Self> { Some(BigInt::from(value)) }
+    fn to_u64(&self) -> Option<u64> { ToPrimitive::to_u64(self) }
+    fn limits() -> Option<(i128, i128)> { None }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Precision {
+    I32,
+    I64,
+    Big,
+}
+
+impl FromStr for Precision {
+    type Err = PrecisionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "i32" => Ok(Precision::I32),
+            "i64" => Ok(Precision::I64),
+            "big" => Ok(Precision::Big),
+            _ => Err(PrecisionError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecisionError(String);
+
+impl Display for PrecisionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown precision '{}', expected i32, i64 or big", self.0)
+    }
+}
+
+impl error::Error for PrecisionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(7, 3, 1)]
+    #[test_case(-7, 3, 2)]
+    #[test_case(-6, 3, 0)]
+    fn rem_euclid_test(value: i64, modulus: i64, expected: i64) {
+        pretty_assertions::assert_eq!(Frequency::rem_euclid(&value, &modulus), expected);
+        let big = Frequency::rem_euclid(&BigInt::from(value), &BigInt::from(modulus));
+        pretty_assertions::assert_eq!(big, BigInt::from(expected));
+    }
+
+    #[test]
+    fn checked_add_test() {
+        assert_eq!(Frequency::checked_add(&i32::MAX, &1), None);
+        assert_eq!(Frequency::checked_add(&(i32::MAX as i64), &1), Some(i32::MAX as i64 + 1));
+    }
+
+    #[test]
+    fn from_i128_test() {
+        assert_eq!(<i32 as Frequency>::from_i128(-5), Some(-5));
+        assert_eq!(<i32 as Frequency>::from_i128(i32::MAX as i128 + 1), None);
+        assert_eq!(<i64 as Frequency>::from_i128(i32::MAX as i128 + 1), Some(i32::MAX as i64 + 1));
+        assert_eq!(<BigInt as Frequency>::from_i128(i128::MIN), Some(BigInt::from(i128::MIN)));
+    }
+
+    #[test_case("i32", Ok(Precision::I32))]
+    #[test_case("big", Ok(Precision::Big))]
+    #[test_case("u8", Err(PrecisionError("u8".to_string())))]
+    fn precision_from_str_test(input: &str, expected: Result<Precision, PrecisionError>) {
+        pretty_assertions::assert_eq!(input.parse::<Precision>(), expected);
+    }
+}
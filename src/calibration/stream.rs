@@ -0,0 +1,299 @@
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+use super::{parse_change, tokens, CalibrationError, Frequency};
+
+pub const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+// Sum of one chunk of whole lines. `low` and `high` are the lowest and highest
+// running sums inside the chunk, relative to the frequency it starts from
+#[derive(Debug, Default)]
+struct ChunkSum {
+    sum: i128,
+    low: i128,
+    high: i128,
+    lines: usize,
+    error: Option<CalibrationError>,
+}
+
+// The same result as `total(&parse_changes(lines)?)` for the lines of `reader`,
+// without keeping the changes around: chunks of whole lines are parsed from bytes
+// and summed on `threads` threads, then combined in order. Like the sequential
+// version, a bad change anywhere wins over an overflow. Only for fixed-width frequencies.
+pub fn parallel_total<F: Frequency>(reader: impl Read + Send, threads: usize, chunk_size: usize) -> Result<F, CalibrationError> {
+    let limits = F::limits().ok_or(CalibrationError::Unbounded)?;
+    let threads = threads.max(1);
+    let chunk_size = chunk_size.max(1);
+
+    thread::scope(|scope| {
+        let (chunk_sender, chunk_receiver) = mpsc::sync_channel::<(usize, Vec<u8>)>(threads * 2);
+        // Once the workers are gone the chunk receiver is dropped, which stops the reader as well
+        let chunk_receiver = Arc::new(Mutex::new(chunk_receiver));
+        let (sum_sender, sum_receiver) = mpsc::channel();
+        for _ in 0..threads {
+            let chunk_receiver = Arc::clone(&chunk_receiver);
+            let sum_sender = sum_sender.clone();
+            scope.spawn(move || {
+                loop {
+                    let job = chunk_receiver.lock().unwrap().recv();
+                    let (index, chunk) = match job {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    };
+                    let sum = sum_chunk::<F>(&chunk, limits);
+                    if sum_sender.send((index, chunk, sum)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sum_sender);
+        drop(chunk_receiver);
+
+        let reader_thread = scope.spawn(move || {
+            let mut reader = reader;
+            let mut carry = Vec::new();
+            let mut index = 0;
+            while let Some(chunk) = read_chunk(&mut reader, &mut carry, chunk_size)? {
+                if chunk_sender.send((index, chunk)).is_err() {
+                    break;
+                }
+                index += 1;
+            }
+            Ok(())
+        });
+        let result = combine::<F>(sum_receiver, limits);
+        reader_thread.join().unwrap().map_err(|error: std::io::Error| CalibrationError::ReadError(error.to_string()))?;
+        result
+    })
+}
+
+// Reads about `chunk_size` bytes, up to and including the last complete line.
+// The rest is kept in `carry` for the next chunk
+fn read_chunk(reader: &mut impl Read, carry: &mut Vec<u8>, chunk_size: usize) -> std::io::Result<Option<Vec<u8>>> {
+    let mut chunk = std::mem::take(carry);
+    loop {
+        let start = chunk.len();
+        chunk.resize(start + chunk_size, 0);
+        let read = reader.read(&mut chunk[start..])?;
+        chunk.truncate(start + read);
+        if read == 0 {
+            return Ok(if chunk.is_empty() { None } else { Some(chunk) });
+        }
+        if let Some(end) = chunk[start..].iter().rposition(|&byte| byte == b'\n') {
+            if chunk.len() >= chunk_size {
+                *carry = chunk.split_off(start + end + 1);
+                return Ok(Some(chunk));
+            }
+        }
+    }
+}
+
+fn combine<F: Frequency>(receiver: mpsc::Receiver<(usize, Vec<u8>, ChunkSum)>, (min, max): (i128, i128)) -> Result<F, CalibrationError> {
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    let (mut total, mut lines) = (0i128, 0);
+    let mut first_overflow = None;
+    for (index, chunk, sum) in receiver {
+        pending.insert(index, (chunk, sum));
+        while let Some((chunk, sum)) = pending.remove(&next) {
+            if let Some(error) = sum.error {
+                return Err(shift_line(error, lines));
+            }
+            if first_overflow.is_none() && (total + sum.low < min || total + sum.high > max) {
+                first_overflow = Some((chunk, total, lines));
+            }
+            total += sum.sum;
+            lines += sum.lines;
+            next += 1;
+        }
+    }
+    match first_overflow {
+        Some((chunk, start, first_line)) => Err(find_overflow::<F>(&chunk, start, first_line, (min, max))),
+        // Within the limits the total always fits, unless they don't describe `F`
+        None => F::from_i128(total).ok_or(CalibrationError::Unbounded),
+    }
+}
+
+fn sum_chunk<F: Frequency>(chunk: &[u8], limits: (i128, i128)) -> ChunkSum {
+    let mut result = ChunkSum::default();
+    let mut sum = 0;
+    let parsed = for_each_change::<F>(chunk, limits, |_, _, change| {
+        sum += change;
+        result.low = result.low.min(sum);
+        result.high = result.high.max(sum);
+    });
+    result.sum = sum;
+    result.lines = chunk.iter().filter(|&&byte| byte == b'\n').count();
+    result.error = parsed.err();
+    result
+}
+
+// Walks the chunk again from the frequency it starts at to find the change that overflows
+fn find_overflow<F: Frequency>(chunk: &[u8], start: i128, first_line: usize, (min, max): (i128, i128)) -> CalibrationError {
+    let mut sum = start;
+    let mut overflow = None;
+    for_each_change::<F>(chunk, (min, max), |line, column, change| {
+        sum += change;
+        if overflow.is_none() && (sum < min || sum > max) {
+            overflow = Some(CalibrationError::Overflow { line: first_line + line, column, change: change.to_string() });
+        }
+    }).unwrap();
+    overflow.unwrap()
+}
+
+// Plain ASCII lines are parsed straight from the bytes. Anything else,
+// including every line with an error, goes through `parse_change` to get the same changes and errors.
+// `visit` gets the line within the chunk, the column and the change
+fn for_each_change<F: Frequency>(chunk: &[u8], limits: (i128, i128), mut visit: impl FnMut(usize, usize, i128)) -> Result<(), CalibrationError> {
+    let mut lines = chunk.split(|&byte| byte == b'\n');
+    if chunk.last() == Some(&b'\n') {
+        lines.next_back();
+    }
+    for (line, bytes) in lines.enumerate() {
+        if parse_line_fast(bytes, limits, &mut |column, change| visit(line, column, change)).is_none() {
+            let text = String::from_utf8_lossy(bytes);
+            for (column, token) in tokens(&text) {
+                let change = parse_change::<F>(line, column, token)?;
+                visit(line, column, change.to_i128().unwrap());
+            }
+        }
+    }
+    Ok(())
+}
+
+// Returns `None`, before visiting anything, when the line needs the slow path.
+// The line is ASCII, so byte offsets are the same as character columns
+fn parse_line_fast(bytes: &[u8], (min, max): (i128, i128), visit: &mut impl FnMut(usize, i128)) -> Option<()> {
+    let is_separator = |byte: u8| byte == b',' || byte.is_ascii_whitespace() || byte == 0x0b;
+    let end = bytes.iter().enumerate()
+        .position(|(i, &byte)| byte == b'#' || (byte == b'/' && bytes.get(i + 1) == Some(&b'/')))
+        .unwrap_or(bytes.len());
+    let bytes = &bytes[..end];
+    if !bytes.is_ascii() {
+        return None;
+    }
+
+    let mut changes = [(0, 0i128); 16];
+    let mut count = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if is_separator(bytes[i]) {
+            i += 1;
+            continue;
+        }
+        let column = i;
+        let negative = bytes[i] == b'-';
+        if matches!(bytes[i], b'+' | b'-') {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b',' && is_separator(bytes[i]) {
+                i += 1;
+            }
+        }
+        let start = i;
+        let mut value: i128 = 0;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            value = value.checked_mul(10)?.checked_add((bytes[i] - b'0') as i128)?;
+            i += 1;
+        }
+        if i == start || (i < bytes.len() && !is_separator(bytes[i])) || count == changes.len() {
+            return None;
+        }
+        let value = if negative { -value } else { value };
+        if value < min || value > max {
+            return None;
+        }
+        changes[count] = (column, value);
+        count += 1;
+    }
+    changes[..count].iter().for_each(|&(column, change)| visit(column, change));
+    Some(())
+}
+
+fn shift_line(error: CalibrationError, lines: usize) -> CalibrationError {
+    match error {
+        CalibrationError::InvalidChange { line, column, text } => CalibrationError::InvalidChange { line: line + lines, column, text },
+        CalibrationError::OutOfRange { line, column, text } => CalibrationError::OutOfRange { line: line + lines, column, text },
+        error => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use crate::calibration::{parse_changes, total};
+    use pretty_assertions::assert_eq;
+
+    fn sequential<F: Frequency>(input: &str) -> Result<F, CalibrationError> {
+        total(&parse_changes(input.lines().map(String::from))?)
+    }
+
+    fn check<F: Frequency>(input: &str) {
+        for threads in [1, 2, 5] {
+            for chunk_size in [1, 3, 16, DEFAULT_CHUNK_SIZE] {
+                let result = parallel_total::<F>(input.as_bytes(), threads, chunk_size);
+                assert_eq!(result, sequential(input), "{:?} with {} threads, chunks of {}", input, threads, chunk_size);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_total_test() {
+        check::<i32>("+1\n-2\n+3\n+1\n");
+        check::<i32>("+1, -2, +3, +1");
+        check::<i32>("# header\n+ 1 -2\r\n\n  +3 // three\n1");
+        check::<i64>("9223372036854775807\n-9223372036854775808\n");
+        check::<i32>("+1\n-\u{a0}2\n+3");
+        check::<i32>("+1\n\u{2003}+3");
+    }
+
+    #[test]
+    fn parallel_total_errors_test() {
+        check::<i32>("+1\n+2\nx3\n+4\ny5");
+        check::<i32>("+1\n+2, 3000000000\n");
+        check::<i32>("+2000000000\n+2000000000\n-2000000000\nx\n");
+        check::<i32>("+2000000000\n+2000000000\n-2000000000\n+2000000000\n+2000000000\n");
+        check::<i32>("-2000000000\n-2000000000\n");
+        check::<i32>("+1\n\n+2147483640, +7,  +1\n");
+        check::<i32>("+1\n+2147483647\u{e9}\n");
+        check::<i32>("");
+        check::<i32>("+1\n+2\u{e9}\n");
+    }
+
+    #[test]
+    fn parallel_total_generated_test() {
+        let mut random = Random::new(17);
+        let mut input = String::new();
+        for line in 0..20_000 {
+            let change = random.below(2_000_001) as i64 - 1_000_000;
+            let separator = if line % 7 == 0 { ", " } else { "\n" };
+            input += &format!("{:+}{}", change, separator);
+        }
+        let expected = sequential::<i64>(&input);
+        for threads in [1, 4] {
+            assert_eq!(parallel_total::<i64>(input.as_bytes(), threads, 4096), expected);
+        }
+        assert_eq!(parallel_total::<i32>(input.as_bytes(), 4, 4096), sequential::<i32>(&input));
+    }
+
+    #[test]
+    fn parallel_total_unbounded_test() {
+        let result = parallel_total::<num_bigint::BigInt>("+1\n".as_bytes(), 2, DEFAULT_CHUNK_SIZE);
+        assert_eq!(result, Err(CalibrationError::Unbounded));
+    }
+
+    #[test]
+    fn read_chunk_test() {
+        let mut reader = "+1\n+22\n+333\n".as_bytes();
+        let mut carry = Vec::new();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = read_chunk(&mut reader, &mut carry, 4).unwrap() {
+            chunks.push(String::from_utf8(chunk).unwrap());
+        }
+        assert_eq!(chunks, vec!["+1\n", "+22\n", "+333\n"]);
+    }
+}
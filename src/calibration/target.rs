@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use super::{first_skipped_overflow, overflow, CalibrationError, Changes, Frequency};
+
+// Answers "when is this frequency first reached" for any number of targets.
+// Step `k * n + i` is at frequency `sums[i] + k * drift`, so a target can only be reached
+// from the sums with its residue modulo the drift, and the closest one below it
+// (above it for a negative drift) needs the fewest passes: a binary search per query.
+// The steps past the first pass are never walked, so the first one that would overflow
+// is worked out up front and every answer at or after it is an error instead.
+pub struct TargetIndex<F> {
+    sums: Vec<F>,
+    drift: F,
+    groups: HashMap<F, Vec<(F, usize)>>,
+    overflow: Option<(i128, CalibrationError)>,
+}
+
+impl<F: Frequency> TargetIndex<F> {
+    pub fn new(changes: &Changes<F>) -> Result<Self, CalibrationError> {
+        if changes.is_empty() {
+            return Err(CalibrationError::EmptyInput);
+        }
+        let mut sums = Vec::with_capacity(changes.len());
+        let mut sum = F::zero();
+        for (index, change) in changes.values().iter().enumerate() {
+            sums.push(sum.clone());
+            sum = sum.checked_add(change).ok_or_else(|| overflow(changes, index))?;
+        }
+        let drift = sum;
+
+        // Each group is sorted by frequency and keeps the earliest step of every frequency
+        let modulus = if drift == F::zero() { None } else { Some(drift.abs()) };
+        let mut groups: HashMap<F, Vec<(F, usize)>> = HashMap::new();
+        for (i, sum) in sums.iter().enumerate() {
+            let residue = modulus.as_ref().map_or_else(F::zero, |modulus| sum.rem_euclid(modulus));
+            groups.entry(residue).or_default().push((sum.clone(), i));
+        }
+        for group in groups.values_mut() {
+            group.sort_unstable();
+            group.dedup_by(|later, earlier| later.0 == earlier.0);
+        }
+        let overflow = if drift == F::zero() { None } else { first_skipped_overflow(changes, &sums, &drift) };
+        Ok(Self { sums, drift, groups, overflow })
+    }
+
+    // The step at which `target` is first reached, step 0 being the initial frequency of 0,
+    // or `None` if it never is
+    pub fn first_step(&self, target: &F) -> Result<Option<u64>, CalibrationError> {
+        let residue = if self.drift == F::zero() { F::zero() } else { target.rem_euclid(&self.drift.abs()) };
+        let group = match self.groups.get(&residue) {
+            Some(group) => group,
+            None => return Ok(None),
+        };
+        let closest = if self.drift == F::zero() {
+            group.binary_search_by(|(sum, _)| sum.cmp(target)).ok().map(|position| &group[position])
+        } else if self.drift.is_negative() {
+            group.get(group.partition_point(|(sum, _)| sum < target))
+        } else {
+            group.partition_point(|(sum, _)| sum <= target).checked_sub(1).map(|position| &group[position])
+        };
+        let (sum, i) = match closest {
+            Some(closest) => closest,
+            None => return Ok(None),
+        };
+
+        let n = self.sums.len() as u64;
+        let step = if self.drift == F::zero() {
+            *i as u64
+        } else {
+            target.checked_sub(sum)
+                .and_then(|distance| distance.checked_div(&self.drift))
+                .and_then(|passes| passes.to_u64())
+                .and_then(|passes| passes.checked_mul(n))
+                .and_then(|step| step.checked_add(*i as u64))
+                .ok_or(CalibrationError::TooManySteps)?
+        };
+        match &self.overflow {
+            Some((overflow_step, error)) if *overflow_step <= step as i128 => Err(error.clone()),
+            _ => Ok(Some(step)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use num_bigint::BigInt;
+    use test_case::test_case;
+
+    // Reference: walks `passes` passes and returns the first step at `target`
+    fn first_step_by_simulation(changes: &[i32], target: i32, passes: usize) -> Option<u64> {
+        let frequencies = changes.iter().cycle().take(changes.len() * passes).scan(0, |sum, change| {
+            *sum += change;
+            Some(*sum)
+        });
+        std::iter::once(0).chain(frequencies).position(|frequency| frequency == target).map(|step| step as u64)
+    }
+
+    // 0 -> 3 -> 1 | -> 4 -> 2 | -> 5 -> 3 ...
+    #[test_case(&[3, -2], 0 => Some(0); "initial frequency")]
+    #[test_case(&[3, -2], 3 => Some(1); "first pass")]
+    #[test_case(&[3, -2], 4 => Some(3); "second pass")]
+    #[test_case(&[3, -2], 100 => Some(195); "many passes")]
+    #[test_case(&[3, -2], -1 => None; "behind the drift")]
+    #[test_case(&[-3, 2], -100 => Some(195); "negative drift")]
+    #[test_case(&[-3, 2], 1 => None; "behind a negative drift")]
+    #[test_case(&[5, -5], 5 => Some(1); "zero drift")]
+    #[test_case(&[5, -5], 4 => None; "zero drift never")]
+    #[test_case(&[4, 2], 7 => None; "wrong residue")]
+    fn first_step_test(changes: &[i32], target: i32) -> Option<u64> {
+        TargetIndex::new(&changes.to_vec().into()).unwrap().first_step(&target).unwrap()
+    }
+
+    #[test]
+    fn first_step_matches_simulation_test() {
+        let mut generator = Random::new(1518);
+        let mut random = move |bound: u64| generator.below(bound);
+        for _ in 0..500 {
+            let length = 1 + random(8) as usize;
+            let changes: Vec<i32> = (0..length).map(|_| random(21) as i32 - 10).collect();
+            let index = TargetIndex::new(&changes.clone().into()).unwrap();
+            for target in -30..=30 {
+                // With drift at least 1 and changes at most 10, 200 passes cover any target in range
+                let expected = first_step_by_simulation(&changes, target, 200);
+                pretty_assertions::assert_eq!(index.first_step(&target).unwrap(), expected, "{:?} {}", changes, target);
+            }
+        }
+    }
+
+    #[test]
+    fn first_step_errors_test() {
+        pretty_assertions::assert_eq!(TargetIndex::<i32>::new(&vec![].into()).err(), Some(CalibrationError::EmptyInput));
+
+        // 0 -> 2000000000 -> 1 | -> 2000000001 ... overflows on the way to 200000000
+        let index = TargetIndex::new(&vec![2_000_000_000, -1_999_999_999].into()).unwrap();
+        let expected = CalibrationError::Overflow { line: 0, column: 0, change: "2000000000".to_string() };
+        pretty_assertions::assert_eq!(index.first_step(&200_000_000), Err(expected));
+        pretty_assertions::assert_eq!(index.first_step(&3), Ok(Some(6)));
+
+        let changes: Vec<BigInt> = vec![2_000_000_000.into(), (-1_999_999_999).into()];
+        let index = TargetIndex::new(&changes.into()).unwrap();
+        pretty_assertions::assert_eq!(index.first_step(&BigInt::from(200_000_000)), Ok(Some(400_000_000)));
+    }
+}
@@ -0,0 +1,184 @@
+use std::{collections::HashMap, fmt::Write};
+use super::{overflow, CalibrationError, Changes, Frequency};
+
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 400.0;
+const CHART_MARGIN: f64 = 20.0;
+const CHART_POINTS: usize = 2000;
+
+// Every step is kept in memory, so longer traces are refused
+pub const MAX_TRACE_STEPS: u64 = 10_000_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TracePoint<F> {
+    pub step: u64,
+    pub pass: u64,
+    pub line: usize,
+    pub column: usize,
+    pub frequency: F,
+}
+
+// The running frequency after each of the first `steps` changes, going around the list as often as needed.
+// `pass_drifts` only has the complete passes, a pass cut short by the end of the trace drifts by `partial_drift`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace<F> {
+    pub points: Vec<TracePoint<F>>,
+    pub pass_drifts: Vec<F>,
+    pub partial_drift: Option<F>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStats<F> {
+    pub min: F,
+    pub max: F,
+    pub pass_drifts: Vec<F>,
+    pub partial_drift: Option<F>,
+    pub repeated: Vec<(F, usize)>,
+}
+
+impl<F: Frequency> Trace<F> {
+    pub fn record(changes: &Changes<F>, steps: u64) -> Result<Self, CalibrationError> {
+        if changes.is_empty() {
+            return Err(CalibrationError::EmptyInput);
+        }
+        if steps > MAX_TRACE_STEPS {
+            return Err(CalibrationError::TraceTooLong { limit: MAX_TRACE_STEPS });
+        }
+        let n = changes.len() as u64;
+        let mut points = Vec::new();
+        let mut pass_drifts = Vec::new();
+        let mut sum = F::zero();
+        let mut drift = F::zero();
+        for (step, (index, change)) in (1..=steps).zip(changes.values().iter().enumerate().cycle()) {
+            sum = sum.checked_add(change).ok_or_else(|| overflow(changes, index))?;
+            drift = drift.checked_add(change).ok_or_else(|| overflow(changes, index))?;
+            let pass = (step - 1) / n;
+            let position = changes.position(index);
+            points.push(TracePoint { step, pass, line: position.line, column: position.column, frequency: sum.clone() });
+            if index + 1 == changes.len() {
+                pass_drifts.push(std::mem::replace(&mut drift, F::zero()));
+            }
+        }
+        let partial_drift = if steps.is_multiple_of(n) { None } else { Some(drift) };
+        Ok(Self { points, pass_drifts, partial_drift })
+    }
+
+    // The initial frequency of 0 counts as a visit as well
+    pub fn stats(&self) -> TraceStats<F> {
+        let frequencies = || std::iter::once(F::zero()).chain(self.points.iter().map(|point| point.frequency.clone()));
+        let mut visits: HashMap<F, usize> = HashMap::new();
+        for frequency in frequencies() {
+            *visits.entry(frequency).or_default() += 1;
+        }
+        let mut repeated: Vec<(F, usize)> = visits.into_iter().filter(|&(_, count)| count > 1).collect();
+        repeated.sort_unstable();
+        TraceStats {
+            min: frequencies().min().unwrap(),
+            max: frequencies().max().unwrap(),
+            pass_drifts: self.pass_drifts.clone(),
+            partial_drift: self.partial_drift.clone(),
+            repeated,
+        }
+    }
+
+    // Passes, lines and columns are counted from 1, like in the rest of the output
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("step,pass,line,column,frequency\n");
+        for TracePoint { step, pass, line, column, frequency } in &self.points {
+            writeln!(csv, "{},{},{},{},{}", step, pass + 1, line + 1, column + 1, frequency).unwrap();
+        }
+        csv
+    }
+
+    // Long traces are thinned out to at most `CHART_POINTS` points, always keeping the last one
+    pub fn to_svg(&self) -> String {
+        let value = |frequency: &F| frequency.to_string().parse::<f64>().unwrap();
+        let stats = self.stats();
+        let (min, max) = (value(&stats.min), value(&stats.max));
+        let last_step = self.points.last().map_or(0, |point| point.step);
+        let x = |step: u64| CHART_MARGIN + (CHART_WIDTH - 2.0 * CHART_MARGIN) * step as f64 / last_step.max(1) as f64;
+        let y = |frequency: f64| {
+            let range = (max - min).max(1.0);
+            CHART_HEIGHT - CHART_MARGIN - (CHART_HEIGHT - 2.0 * CHART_MARGIN) * (frequency - min) / range
+        };
+
+        let stride = self.points.len().div_ceil(CHART_POINTS).max(1);
+        let mut polyline = format!("{:.1},{:.1}", x(0), y(0.0));
+        let sampled = self.points.iter().enumerate()
+            .filter(|&(i, _)| (i + 1) % stride == 0 || i + 1 == self.points.len());
+        for (_, point) in sampled {
+            write!(polyline, " {:.1},{:.1}", x(point.step), y(value(&point.frequency))).unwrap();
+        }
+
+        let mut svg = String::new();
+        writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {0} {1}">"#,
+                 CHART_WIDTH, CHART_HEIGHT).unwrap();
+        writeln!(svg, "<title>Frequency over {} steps, from {} to {}</title>", last_step, stats.min, stats.max).unwrap();
+        writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+        writeln!(svg, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{1:.1}" stroke="gray" stroke-dasharray="4"/>"#,
+                 x(0), y(0.0), x(last_step)).unwrap();
+        writeln!(svg, r#"<polyline points="{}" fill="none" stroke="steelblue"/>"#, polyline).unwrap();
+        writeln!(svg, "</svg>").unwrap();
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn record_test() {
+        // 0 -> 3 -> 1 | -> 4 -> 2 | -> 5
+        let trace = Trace::record(&vec![3, -2].into(), 5).unwrap();
+        let frequencies: Vec<i32> = trace.points.iter().map(|point| point.frequency).collect();
+        assert_eq!(frequencies, vec![3, 1, 4, 2, 5]);
+        assert_eq!(trace.points[3], TracePoint { step: 4, pass: 1, line: 1, column: 0, frequency: 2 });
+        assert_eq!(trace.pass_drifts, vec![1, 1]);
+        assert_eq!(trace.partial_drift, Some(3));
+        assert_eq!(Trace::record(&vec![3, -2].into(), 4).unwrap().partial_drift, None);
+    }
+
+    #[test]
+    fn record_errors_test() {
+        assert_eq!(Trace::<i32>::record(&vec![].into(), 3), Err(CalibrationError::EmptyInput));
+        let result = Trace::record(&vec![i32::MAX, 0].into(), 3);
+        assert_eq!(result, Err(CalibrationError::Overflow { line: 0, column: 0, change: i32::MAX.to_string() }));
+        let result = Trace::record(&vec![1].into(), 100_000_000_000);
+        assert_eq!(result, Err(CalibrationError::TraceTooLong { limit: MAX_TRACE_STEPS }));
+    }
+
+    #[test]
+    fn stats_test() {
+        // 0 -> 3 -> 1 -> -1 | -> 2 -> 0 -> -2 | -> 1
+        let trace = Trace::record(&vec![3, -2, -2].into(), 7).unwrap();
+        let expected = TraceStats { min: -2, max: 3, pass_drifts: vec![-1, -1], partial_drift: Some(3), repeated: vec![(0, 2), (1, 2)] };
+        assert_eq!(trace.stats(), expected);
+    }
+
+    #[test]
+    fn to_csv_test() {
+        let trace = Trace::record(&vec![3, -2].into(), 3).unwrap();
+        assert_eq!(trace.to_csv(), "step,pass,line,column,frequency\n1,1,1,1,3\n2,1,2,1,1\n3,2,1,1,4\n");
+        let changes = crate::calibration::parse_changes::<i32>(std::iter::once("+3, -2".to_string())).unwrap();
+        let trace = Trace::record(&changes, 2).unwrap();
+        assert_eq!(trace.to_csv(), "step,pass,line,column,frequency\n1,1,1,1,3\n2,1,1,5,1\n");
+    }
+
+    #[test]
+    fn to_svg_test() {
+        let svg = Trace::record(&vec![3, -2].into(), 3).unwrap().to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("<title>Frequency over 3 steps, from 0 to 4</title>"));
+        assert!(svg.contains(r#"points="20.0,380.0 273.3,110.0 526.7,290.0 780.0,20.0""#), "{}", svg);
+    }
+
+    #[test]
+    fn to_svg_thins_out_long_traces_test() {
+        let svg = Trace::record(&vec![1].into(), 10_000).unwrap().to_svg();
+        let points = svg.split(r#"points=""#).nth(1).unwrap().split('"').next().unwrap();
+        assert_eq!(points.split(' ').count(), CHART_POINTS + 1);
+        assert!(points.ends_with("780.0,20.0"));
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+};
+
+// The sequence visits `start` states before entering the loop,
+// and then repeats every `period` steps beginning with `state`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Cycle<T> {
+    pub start: usize,
+    pub period: usize,
+    pub state: T,
+}
+
+impl<T> Cycle<T> {
+    // Earliest step that is guaranteed to have the same state as step `n`
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+// Works for any sequence, not only for iterated functions: `start` is the index
+// where the first repeated value was seen, and `period` is the distance to its repeat
+pub fn find_repeat<T: Hash + Eq>(states: impl IntoIterator<Item=T>) -> Option<Cycle<T>> {
+    let mut seen = HashMap::new();
+    for (i, state) in states.into_iter().enumerate() {
+        if let Some(&start) = seen.get(&state) {
+            return Some(Cycle { start, period: i - start, state });
+        }
+        seen.insert(state, i);
+    }
+    None
+}
+
+pub fn find_cycle<T: Hash + Eq + Clone>(initial: T, mut step: impl FnMut(&T) -> T) -> Cycle<T> {
+    let states = std::iter::successors(Some(initial), |state| Some(step(state)));
+    find_repeat(states).unwrap()
+}
+
+// Constant memory, but `step` is called about three times as often as in `find_cycle`
+pub fn floyd<T: PartialEq + Clone>(initial: T, step: impl Fn(&T) -> T) -> Cycle<T> {
+    let mut tortoise = step(&initial);
+    let mut hare = step(&step(&initial));
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+
+    Cycle { start, period, state: tortoise }
+}
+
+// Constant memory, finds the period first and needs fewer `step` calls than `floyd`
+pub fn brent<T: PartialEq + Clone>(initial: T, step: impl Fn(&T) -> T) -> Cycle<T> {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, period, state: tortoise }
+}
+
+pub fn state_at<T: Hash + Eq + Clone>(initial: T, mut step: impl FnMut(&T) -> T, n: usize) -> T {
+    let mut history = vec![initial.clone()];
+    let mut seen = HashMap::new();
+    seen.insert(initial, 0);
+    loop {
+        let last = history.len() - 1;
+        if last == n {
+            return history.pop().unwrap();
+        }
+        let next = step(&history[last]);
+        if let Some(&start) = seen.get(&next) {
+            let period = history.len() - start;
+            return history.swap_remove(start + (n - start) % period);
+        }
+        seen.insert(next.clone(), history.len());
+        history.push(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+    use pretty_assertions::assert_eq;
+
+    // 3 -> 10 -> 101 -> 2 -> 5 -> 26 -> 167 -> 95 -> 101 -> ...
+    fn step(x: &u64) -> u64 {
+        (x * x + 1) % 255
+    }
+
+    const EXPECTED: Cycle<u64> = Cycle { start: 2, period: 6, state: 101 };
+
+    #[test]
+    fn find_cycle_test() {
+        assert_eq!(find_cycle(3, step), EXPECTED);
+    }
+
+    #[test]
+    fn floyd_test() {
+        assert_eq!(floyd(3, step), EXPECTED);
+    }
+
+    #[test]
+    fn brent_test() {
+        assert_eq!(brent(3, step), EXPECTED);
+    }
+
+    #[test]
+    fn algorithms_agree_test() {
+        for initial in 0..255 {
+            let expected = find_cycle(initial, step);
+            assert_eq!(floyd(initial, step), expected);
+            assert_eq!(brent(initial, step), expected);
+        }
+    }
+
+    #[test]
+    fn fixed_point_test() {
+        let expected = Cycle { start: 0, period: 1, state: 7 };
+        assert_eq!(find_cycle(7, |x| *x), expected);
+        assert_eq!(floyd(7, |x| *x), expected);
+        assert_eq!(brent(7, |x| *x), expected);
+    }
+
+    #[test]
+    fn find_repeat_test() {
+        let result = find_repeat(vec![0, 1, 5, 3, 5, 1]);
+        assert_eq!(result, Some(Cycle { start: 2, period: 2, state: 5 }));
+        assert_eq!(find_repeat(vec![1, 2, 3]), None);
+    }
+
+    #[test_case(0, 0)]
+    #[test_case(2, 2)]
+    #[test_case(7, 7)]
+    #[test_case(8, 2)]
+    #[test_case(1_000_000_000, 4)]
+    fn equivalent_step_test(n: usize, expected: usize) {
+        pretty_assertions::assert_eq!(EXPECTED.equivalent_step(n), expected);
+    }
+
+    #[test]
+    fn state_at_test() {
+        let mut state = 3;
+        for n in 0..50 {
+            assert_eq!(state_at(3, step, n), state);
+            state = step(&state);
+        }
+        assert_eq!(state_at(3, step, 1_000_000_000), 5);
+    }
+}
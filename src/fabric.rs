@@ -0,0 +1,186 @@
+mod claim;
+mod overlaps;
+
+use std::{
+    error,
+    fmt::{Display, Formatter},
+};
+use crate::grid::{Bounds, DenseGrid, Grid, Point};
+
+pub use claim::{Claim, ClaimError};
+pub use overlaps::Overlaps;
+
+// Largest fabric, in squares, that gets a dense grid: one u64 per square, 256 MiB at most
+pub const MAX_AREA: u64 = 1 << 25;
+
+// How many claims cover each square, over the smallest bounds around all claims
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Fabric {
+    // Summed-area table: `sums[p]` adds up the counts from the top-left corner of the fabric to `p`
+    sums: DenseGrid<u64>,
+    // `histogram[c]` squares are covered by exactly `c` claims
+    histogram: Vec<u64>,
+}
+
+impl Fabric {
+    // Every claim only marks the corners of a 2D difference array in the one grid;
+    // its prefix sums, taken in place, are the counts, and theirs the summed-area table
+    pub fn new(claims: &[Bounds]) -> Result<Self, FabricError> {
+        let bounds = claims.iter().copied().reduce(|a, b| a.union(&b)).ok_or(FabricError::NoClaims)?;
+        let area = bounds.width() as u128 * bounds.height() as u128;
+        if area > MAX_AREA as u128 {
+            return Err(FabricError::TooLarge(area));
+        }
+
+        // The differences go negative, but wrap back once summed up
+        let mut sums = DenseGrid::new(bounds, 0u64);
+        for claim in claims {
+            let end = claim.max + Point::new(1, 1);
+            let corners = [
+                (claim.min, 1),
+                (Point::new(end.x, claim.min.y), -1),
+                (Point::new(claim.min.x, end.y), -1),
+                (end, 1),
+            ];
+            for (corner, delta) in corners.iter() {
+                if let Some(cell) = sums.get_mut(*corner) {
+                    *cell = cell.wrapping_add(*delta as u64);
+                }
+            }
+        }
+
+        prefix_sums(&mut sums, bounds);
+        let mut histogram = vec![0; claims.len() + 1];
+        for point in bounds.points() {
+            histogram[sums[point] as usize] += 1;
+        }
+        prefix_sums(&mut sums, bounds);
+        Ok(Self { sums, histogram })
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.sums.bounds().unwrap()
+    }
+
+    // Squares outside the fabric aren't claimed at all
+    pub fn count(&self, point: Point) -> u32 {
+        self.sum(&Bounds::new(point, point)) as u32
+    }
+
+    // Squares covered by at least `threshold` claims, and by at least one
+    pub fn overlap_area(&self, threshold: usize) -> u64 {
+        self.histogram.iter().skip(threshold.max(1)).sum()
+    }
+
+    // Sum of the counts over `rectangle`; only the part inside the fabric adds anything
+    pub fn sum(&self, rectangle: &Bounds) -> u64 {
+        let inside = match rectangle.intersection(&self.bounds()) {
+            Some(inside) => inside,
+            None => return 0,
+        };
+        let sum_to = |x, y| self.sums.get(Point::new(x, y)).copied().unwrap_or(0);
+        let (min, max) = (inside.min, inside.max);
+        sum_to(max.x, max.y) + sum_to(min.x - 1, min.y - 1) - sum_to(min.x - 1, max.y) - sum_to(max.x, min.y - 1)
+    }
+}
+
+// Replaces every cell of `grid` with the sum of the cells from the top-left corner to it
+fn prefix_sums(grid: &mut DenseGrid<u64>, bounds: Bounds) {
+    for point in bounds.points() {
+        let at = |point| grid.get(point).copied().unwrap_or(0u64);
+        let sum = at(point - Point::new(1, 0)).wrapping_add(at(point - Point::new(0, 1)))
+            .wrapping_sub(at(point - Point::new(1, 1)));
+        grid[point] = grid[point].wrapping_add(sum);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FabricError {
+    NoClaims,
+    TooLarge(u128),
+}
+
+impl Display for FabricError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FabricError::NoClaims => write!(f, "No claims to lay out on the fabric"),
+            FabricError::TooLarge(area) => {
+                write!(f, "Fabric of {} squares is too large for a dense grid, the limit is {}", area, MAX_AREA)
+            }
+        }
+    }
+}
+
+impl error::Error for FabricError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use crate::grid::covered_area;
+    use pretty_assertions::assert_eq;
+
+    fn claims() -> Vec<Bounds> {
+        vec![
+            Bounds::new(Point::new(1, 3), Point::new(4, 6)),
+            Bounds::new(Point::new(3, 1), Point::new(6, 4)),
+            Bounds::new(Point::new(5, 5), Point::new(6, 6)),
+            Bounds::new(Point::new(2, 2), Point::new(3, 3)),
+        ]
+    }
+
+    #[test]
+    fn count_test() {
+        let fabric = Fabric::new(&claims()).unwrap();
+        assert_eq!(fabric.bounds(), Bounds::new(Point::new(1, 1), Point::new(6, 6)));
+        let counts: Vec<u32> = [(3, 3), (4, 4), (2, 3), (5, 5), (1, 1), (0, 0), (7, 3)].iter()
+            .map(|&(x, y)| fabric.count(Point::new(x, y)))
+            .collect();
+        assert_eq!(counts, vec![3, 2, 2, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn overlap_area_test() {
+        let fabric = Fabric::new(&claims()).unwrap();
+        let areas: Vec<u64> = (0..=4).map(|threshold| fabric.overlap_area(threshold)).collect();
+        assert_eq!(areas, vec![33, 33, 6, 1, 0]);
+    }
+
+    #[test]
+    fn sum_test() {
+        let fabric = Fabric::new(&claims()).unwrap();
+        let sums: Vec<u64> = claims().iter().map(|claim| fabric.sum(claim)).collect();
+        assert_eq!(sums, vec![22, 22, 4, 8]);
+        assert_eq!(fabric.sum(&Bounds::new(Point::new(-5, -5), Point::new(10, 10))), 40);
+        assert_eq!(fabric.sum(&Bounds::new(Point::new(7, 0), Point::new(9, 9))), 0);
+    }
+
+    #[test]
+    fn matches_sweep_test() {
+        let mut generator = Random::new(49);
+        let mut random = move |bound: u64| generator.below(bound) as i64;
+        for _ in 0..200 {
+            let claims: Vec<Bounds> = (0..1 + random(12))
+                .map(|_| {
+                    let min = Point::new(random(20) - 5, random(20) - 5);
+                    Bounds::new(min, min + Point::new(random(8), random(8)))
+                })
+                .collect();
+            let fabric = Fabric::new(&claims).unwrap();
+            for threshold in 1..=3 {
+                assert_eq!(fabric.overlap_area(threshold) as u128, covered_area(&claims, threshold), "{:?}", claims);
+            }
+            let corner = Point::new(random(30) - 10, random(30) - 10);
+            let window = Bounds::new(corner, corner + Point::new(random(15), random(15)));
+            let expected: u64 = window.points().map(|point| fabric.count(point) as u64).sum();
+            assert_eq!(fabric.sum(&window), expected);
+        }
+    }
+
+    #[test]
+    fn errors_test() {
+        assert_eq!(Fabric::new(&[]), Err(FabricError::NoClaims));
+        let huge = Bounds::new(Point::new(0, 0), Point::new(99_999, 99_999));
+        assert_eq!(Fabric::new(&[huge]), Err(FabricError::TooLarge(10_000_000_000)));
+    }
+}
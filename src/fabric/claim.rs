@@ -0,0 +1,234 @@
+use std::{
+    error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use crate::{
+    grid::{Bounds, Point},
+    FromRegex,
+};
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct Claim {
+    pub id: u32,
+    bounds: Bounds,
+}
+
+// #1 @ 1,3: 4x4
+#[derive(FromRegex)]
+#[regex(r"(?x)^
+    \#(?P<id>\d+)
+    \s@\s
+    (?P<x>\d+),
+    (?P<y>\d+):\s
+    (?P<width>\d+)
+    x
+    (?P<height>\d+)$", error = "ClaimLineError")]
+struct ClaimLine {
+    id: u32,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Claim {
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    pub fn intersects_with(&self, other: &Claim) -> bool {
+        self.bounds.intersects(&other.bounds)
+    }
+
+    pub fn intersection(&self, other: &Claim) -> Option<Claim> {
+        self.bounds.intersection(&other.bounds).map(|bounds| Self { id: 0, bounds })
+    }
+
+    fn area(&self) -> u64 {
+        self.bounds.area()
+    }
+
+    pub fn squares(&self) -> Vec<Point> {
+        let mut result = Vec::with_capacity(self.area() as usize);
+        result.extend(self.bounds.columns().flatten());
+        result
+    }
+}
+
+// Every square of a claim has to fit in `u32` coordinates
+impl FromStr for Claim {
+    type Err = ClaimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = s.parse::<ClaimLine>().map_err(|error| ClaimError::new(error, s))?;
+        if line.width == 0 || line.height == 0 {
+            return Err(ClaimError::ZeroSize(s.to_string()));
+        }
+        let right = line.x.checked_add(line.width - 1);
+        let bottom = line.y.checked_add(line.height - 1);
+        match (right, bottom) {
+            (Some(right), Some(bottom)) => Ok(Self {
+                id: line.id,
+                bounds: Bounds::new(
+                    Point::new(line.x as i64, line.y as i64),
+                    Point::new(right as i64, bottom as i64),
+                ),
+            }),
+            _ => Err(ClaimError::OutOfRange(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClaimError {
+    FormatError(String),
+    NumberOverflow { field: &'static str, line: String },
+    ZeroSize(String),
+    OutOfRange(String),
+}
+
+impl ClaimError {
+    // The regex only lets digits through, so a field can only fail by being too large
+    fn new(error: ClaimLineError, line: &str) -> Self {
+        let field = match error {
+            ClaimLineError::FormatError => return ClaimError::FormatError(line.to_string()),
+            ClaimLineError::IdError(_) => "id",
+            ClaimLineError::XError(_) => "x",
+            ClaimLineError::YError(_) => "y",
+            ClaimLineError::WidthError(_) => "width",
+            ClaimLineError::HeightError(_) => "height",
+        };
+        ClaimError::NumberOverflow { field, line: line.to_string() }
+    }
+}
+
+impl Display for ClaimError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClaimError::FormatError(line) => write!(f, "Wrong line format: '{}'", line),
+            ClaimError::NumberOverflow { field, line } => write!(f, "Wrong {}, number too large: '{}'", field, line),
+            ClaimError::ZeroSize(line) => write!(f, "Claim has no squares: '{}'", line),
+            ClaimError::OutOfRange(line) => write!(f, "Claim reaches past the largest coordinate: '{}'", line),
+        }
+    }
+}
+
+impl error::Error for ClaimError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    fn claim(s: &str) -> Claim {
+        s.parse().unwrap()
+    }
+
+    const CORRECT_INPUT: &str = "#1 @ 2,3: 5x4";
+
+    #[test]
+    fn from_str_correct_test() {
+        let result = CORRECT_INPUT.parse::<Claim>().unwrap();
+        let expected = Claim {
+            id: 1,
+            bounds: Bounds::new(Point::new(2, 3), Point::new(6, 6)),
+        };
+        assert_eq!(result, expected);
+    }
+
+    const INCORRECT_FORMAT_INPUT: &str = "@1 # 2,3: 5x4";
+
+    #[test]
+    fn from_str_incorrect_format_test() {
+        let result = INCORRECT_FORMAT_INPUT.parse::<Claim>().unwrap_err();
+        assert_eq!(result, ClaimError::FormatError(INCORRECT_FORMAT_INPUT.to_string()));
+    }
+
+    const INCORRECT_FIELD_INPUT: &str = "#99999999999 @ 2,3: 5x4";
+
+    #[test]
+    fn from_str_incorrect_field_test() {
+        let result = INCORRECT_FIELD_INPUT.parse::<Claim>().unwrap_err();
+        assert_eq!(result, ClaimError::NumberOverflow { field: "id", line: INCORRECT_FIELD_INPUT.to_string() });
+        assert_eq!(result.to_string(), "Wrong id, number too large: '#99999999999 @ 2,3: 5x4'");
+    }
+
+    #[test_case("#1 @ 2,3: 0x4", ClaimError::ZeroSize("#1 @ 2,3: 0x4".to_string()))]
+    #[test_case("#1 @ 2,3: 5x0", ClaimError::ZeroSize("#1 @ 2,3: 5x0".to_string()))]
+    #[test_case("#1 @ 2,4294967295: 1x2", ClaimError::OutOfRange("#1 @ 2,4294967295: 1x2".to_string()))]
+    #[test_case("#1 @ 4294967290,3: 7x4", ClaimError::OutOfRange("#1 @ 4294967290,3: 7x4".to_string()))]
+    #[test_case("#1 @ 2,3: 4294967296x4", ClaimError::NumberOverflow { field: "width", line: "#1 @ 2,3: 4294967296x4".to_string() })]
+    fn from_str_invalid_claim_test(input: &str, expected: ClaimError) {
+        pretty_assertions::assert_eq!(input.parse::<Claim>().unwrap_err(), expected);
+    }
+
+    #[test]
+    fn from_str_largest_claim_test() {
+        let result = "#1 @ 4294967290,0: 6x1".parse::<Claim>().unwrap();
+        assert_eq!(result.bounds, Bounds::new(Point::new(4294967290, 0), Point::new(4294967295, 0)));
+    }
+
+    const INTERSECTION_SOME: (&str, &str) = (
+        "#1 @ 1,3: 4x4",
+        "#2 @ 3,1: 4x4"
+    );
+    const INTERSECTION_NONE: (&str, &str) = (
+        "#1 @ 1,3: 4x4",
+        "#3 @ 5,5: 2x2"
+    );
+
+    #[test_case(INTERSECTION_SOME, true)]
+    #[test_case(INTERSECTION_NONE, false)]
+    fn intersects_with_test((a, b): (&str, &str), result: bool) {
+        let a = claim(a);
+        let b = claim(b);
+        pretty_assertions::assert_eq!(a.intersects_with(&b), result);
+    }
+
+    #[test]
+    fn intersection_some_test() {
+        let a = claim(INTERSECTION_SOME.0);
+        let b = claim(INTERSECTION_SOME.1);
+        let result = a.intersection(&b);
+
+        let expected = Some(Claim {
+            id: 0,
+            bounds: Bounds::new(Point::new(3, 3), Point::new(4, 4)),
+        });
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn intersection_none_test() {
+        let a = claim(INTERSECTION_NONE.0);
+        let b = claim(INTERSECTION_NONE.1);
+        let result = a.intersection(&b);
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn area_test() {
+        let claim = claim(CORRECT_INPUT);
+        let result = claim.area();
+
+        assert_eq!(result, 20)
+    }
+
+    const SQUARES_INPUT: &str = "#1 @ 2,3: 2x3";
+    const SQUARES_RESULT: [Point; 6] = [
+        Point::new(2, 3), Point::new(2, 4), Point::new(2, 5),
+        Point::new(3, 3), Point::new(3, 4), Point::new(3, 5),
+    ];
+
+
+    #[test]
+    fn squares_test() {
+        let claim = claim(SQUARES_INPUT);
+        let result = claim.squares();
+
+        assert_eq!(result, SQUARES_RESULT);
+    }
+}
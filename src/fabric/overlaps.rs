@@ -0,0 +1,181 @@
+use std::collections::{BTreeSet, HashSet};
+use crate::grid::Bounds;
+
+// Which rectangles overlap which, found by sweeping a line across x. A rectangle reaching
+// the line overlaps the ones crossing it whose y interval either contains its top row or
+// starts further down inside it: the first are stabbed in a segment tree, the second are
+// a range of the crossing rectangles ordered by their top. Takes O((n + k) log n) for k overlaps
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Overlaps {
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl Overlaps {
+    pub fn new(rectangles: &[Bounds]) -> Self {
+        let mut ys: Vec<i64> = rectangles.iter()
+            .flat_map(|rectangle| vec![rectangle.min.y, rectangle.max.y + 1])
+            .collect();
+        ys.sort_unstable();
+        ys.dedup();
+        let mut tree = StabbingTree::new(ys);
+        let mut tops = BTreeSet::new();
+
+        // At the same x, rectangles leave the line before others reach it
+        let mut events: Vec<(i64, bool, usize)> = rectangles.iter().enumerate()
+            .flat_map(|(i, rectangle)| vec![(rectangle.min.x, true, i), (rectangle.max.x + 1, false, i)])
+            .collect();
+        events.sort_unstable();
+
+        let mut neighbours = vec![Vec::new(); rectangles.len()];
+        for (_, reaches, i) in events {
+            let y = rectangles[i].y_interval();
+            if reaches {
+                let mut found = tree.stab(y.start);
+                found.extend(tops.range((y.start + 1, 0)..(y.end, 0)).map(|&(_, j)| j));
+                for j in found {
+                    neighbours[i].push(j);
+                    neighbours[j].push(i);
+                }
+                tree.update(y.start, y.end, i, true);
+                tops.insert((y.start, i));
+            } else {
+                tree.update(y.start, y.end, i, false);
+                tops.remove(&(y.start, i));
+            }
+        }
+        neighbours.iter_mut().for_each(|list| list.sort_unstable());
+        Self { neighbours }
+    }
+
+    // Indices of the rectangles overlapping the one at `index`, in order
+    pub fn neighbours(&self, index: usize) -> &[usize] {
+        &self.neighbours[index]
+    }
+
+    // Indices of the rectangles overlapping no other one, in order
+    pub fn alone(&self) -> impl Iterator<Item=usize> + '_ {
+        (0..self.neighbours.len()).filter(move |&i| self.neighbours[i].is_empty())
+    }
+}
+
+// Segment tree over the pieces between consecutive `ys`. An interval is kept in the
+// nodes that exactly cover it, so the nodes on the way down to a point hold each
+// interval containing that point once
+struct StabbingTree {
+    ys: Vec<i64>,
+    nodes: Vec<HashSet<usize>>,
+}
+
+impl StabbingTree {
+    fn new(ys: Vec<i64>) -> Self {
+        let pieces = ys.len().saturating_sub(1).max(1);
+        Self { ys, nodes: vec![HashSet::new(); 4 * pieces] }
+    }
+
+    fn pieces(&self) -> usize {
+        self.ys.len().saturating_sub(1).max(1)
+    }
+
+    // `start` and `end` have to be among the `ys`
+    fn update(&mut self, start: i64, end: i64, id: usize, insert: bool) {
+        let from = self.ys.binary_search(&start).unwrap();
+        let to = self.ys.binary_search(&end).unwrap();
+        self.update_node(1, (0, self.pieces()), (from, to), id, insert);
+    }
+
+    fn update_node(&mut self, node: usize, (low, high): (usize, usize), (from, to): (usize, usize), id: usize, insert: bool) {
+        if to <= low || high <= from {
+            return;
+        }
+        if from <= low && high <= to {
+            if insert {
+                self.nodes[node].insert(id);
+            } else {
+                self.nodes[node].remove(&id);
+            }
+            return;
+        }
+        let middle = (low + high) / 2;
+        self.update_node(2 * node, (low, middle), (from, to), id, insert);
+        self.update_node(2 * node + 1, (middle, high), (from, to), id, insert);
+    }
+
+    fn stab(&self, y: i64) -> Vec<usize> {
+        let piece = self.ys.partition_point(|&start| start <= y).saturating_sub(1);
+        let (mut node, mut low, mut high) = (1, 0, self.pieces());
+        let mut found: Vec<usize> = self.nodes[node].iter().copied().collect();
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if piece < middle {
+                node *= 2;
+                high = middle;
+            } else {
+                node = 2 * node + 1;
+                low = middle;
+            }
+            found.extend(&self.nodes[node]);
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use crate::grid::Point;
+    use pretty_assertions::assert_eq;
+
+    fn bounds(x: i64, y: i64, width: i64, height: i64) -> Bounds {
+        Bounds::new(Point::new(x, y), Point::new(x + width - 1, y + height - 1))
+    }
+
+    #[test]
+    fn overlaps_test() {
+        let rectangles = [bounds(1, 3, 4, 4), bounds(3, 1, 4, 4), bounds(5, 5, 2, 2), bounds(2, 2, 2, 2), bounds(7, 1, 1, 1)];
+        let overlaps = Overlaps::new(&rectangles);
+        let neighbours: Vec<&[usize]> = (0..rectangles.len()).map(|i| overlaps.neighbours(i)).collect();
+        assert_eq!(neighbours, vec![&[1, 3][..], &[0, 3], &[], &[0, 1], &[]]);
+        assert_eq!(overlaps.alone().collect::<Vec<_>>(), vec![2, 4]);
+    }
+
+    #[test]
+    fn touching_rectangles_test() {
+        let rectangles = [bounds(0, 0, 2, 2), bounds(2, 0, 2, 2), bounds(0, 2, 2, 2), bounds(1, 1, 1, 1)];
+        let overlaps = Overlaps::new(&rectangles);
+        assert_eq!(overlaps.neighbours(0), &[3]);
+        assert_eq!(overlaps.alone().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(Overlaps::new(&[]).alone().count(), 0);
+    }
+
+    #[test]
+    fn matches_pairs_test() {
+        let mut generator = Random::new(50);
+        let mut random = move |bound: u64| generator.below(bound) as i64;
+        for _ in 0..200 {
+            let rectangles: Vec<Bounds> = (0..random(25))
+                .map(|_| bounds(random(30) - 10, random(30) - 10, 1 + random(8), 1 + random(8)))
+                .collect();
+            let overlaps = Overlaps::new(&rectangles);
+            for (i, rectangle) in rectangles.iter().enumerate() {
+                let expected: Vec<usize> = (0..rectangles.len())
+                    .filter(|&j| j != i && rectangle.intersects(&rectangles[j]))
+                    .collect();
+                assert_eq!(overlaps.neighbours(i), &expected[..], "{:?}", rectangles);
+            }
+        }
+    }
+
+    // A lattice of 90 000 separate claims, and one more across the first rows of it
+    #[test]
+    fn many_rectangles_test() {
+        let mut rectangles: Vec<Bounds> = (0..300)
+            .flat_map(|row| (0..300).map(move |column| bounds(3 * column, 3 * row, 2, 2)))
+            .collect();
+        rectangles.push(bounds(1, 1, 1000, 7));
+        let overlaps = Overlaps::new(&rectangles);
+        assert_eq!(overlaps.alone().count(), 90_000 - 3 * 300);
+        assert_eq!(overlaps.neighbours(90_000).len(), 3 * 300);
+        assert_eq!(overlaps.neighbours(0), &[90_000]);
+    }
+}
@@ -0,0 +1,382 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    error,
+    fmt::{Debug, Display, Formatter},
+    hash::Hash,
+    ops::Add,
+};
+use GraphError::CycleFound;
+
+// Walks back from `goal` to the node without a parent
+pub fn build_path<N: Hash + Eq + Clone>(parents: &HashMap<N, N>, goal: &N) -> Vec<N> {
+    let mut path = vec![goal.clone()];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+pub fn bfs_distances<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> HashMap<N, usize>
+    where N: Hash + Eq + Clone, I: IntoIterator<Item=N> {
+    let mut distances = HashMap::new();
+    distances.insert(start.clone(), 0);
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node];
+        for next in neighbours(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+// Among the nearest goals the smallest one is chosen, and every step of the path
+// is the smallest node that still lies on a shortest path to it; with `Point`
+// nodes that is the reading order
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+    where N: Hash + Eq + Ord + Clone, I: IntoIterator<Item=N> {
+    let mut distances = HashMap::new();
+    let mut predecessors: HashMap<N, Vec<N>> = HashMap::new();
+    let mut layer = vec![start.clone()];
+    distances.insert(start.clone(), 0);
+
+    let goal = loop {
+        let mut goals: Vec<&N> = layer.iter().filter(|node| is_goal(node)).collect();
+        goals.sort();
+        if let Some(goal) = goals.first() {
+            break (*goal).clone();
+        }
+
+        let mut next_layer = Vec::new();
+        for node in &layer {
+            let distance = distances[node];
+            for next in neighbours(node) {
+                match distances.get(&next) {
+                    None => {
+                        distances.insert(next.clone(), distance + 1);
+                        predecessors.insert(next.clone(), vec![node.clone()]);
+                        next_layer.push(next);
+                    }
+                    Some(&d) if d == distance + 1 => {
+                        predecessors.get_mut(&next).unwrap().push(node.clone());
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if next_layer.is_empty() {
+            return None;
+        }
+        layer = next_layer;
+    };
+
+    let mut on_path = HashSet::new();
+    let mut stack = vec![goal.clone()];
+    while let Some(node) = stack.pop() {
+        if on_path.insert(node.clone()) {
+            stack.extend(predecessors.get(&node).into_iter().flatten().cloned());
+        }
+    }
+
+    let mut path = vec![start];
+    while *path.last().unwrap() != goal {
+        let node = path.last().unwrap();
+        let distance = distances[node];
+        let next = neighbours(node).into_iter()
+            .filter(|next| on_path.contains(next) && distances[next] == distance + 1)
+            .min()
+            .unwrap();
+        path.push(next);
+    }
+    Some(path)
+}
+
+pub fn dijkstra<N, C, I>(
+    start: N,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+    where N: Hash + Eq + Clone, C: Ord + Copy + Default + Add<Output=C>, I: IntoIterator<Item=(N, C)> {
+    astar(start, neighbours, |_| C::default(), is_goal)
+}
+
+// Costs and parents of every node reachable from `start`
+pub fn dijkstra_all<N, C, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> HashMap<N, (C, Option<N>)>
+    where N: Hash + Eq + Clone, C: Ord + Copy + Default + Add<Output=C>, I: IntoIterator<Item=(N, C)> {
+    let mut result = HashMap::new();
+    let mut best = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    let mut nodes = vec![(start.clone(), None)];
+    best.insert(start, C::default());
+    heap.push(Reverse((C::default(), 0)));
+
+    while let Some(Reverse((cost, index))) = heap.pop() {
+        let (node, parent): (N, Option<N>) = nodes[index].clone();
+        if result.contains_key(&node) || best[&node] < cost {
+            continue;
+        }
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if !result.contains_key(&next) && best.get(&next).is_none_or(|&c| next_cost < c) {
+                best.insert(next.clone(), next_cost);
+                nodes.push((next, Some(node.clone())));
+                heap.push(Reverse((next_cost, nodes.len() - 1)));
+            }
+        }
+        result.insert(node, (cost, parent));
+    }
+    result
+}
+
+// `heuristic` must be consistent: for every edge, `heuristic(node) <= cost + heuristic(next)`, and 0 at the goal.
+// Closed nodes are never reopened, so a heuristic that is only admissible may miss the cheapest path
+pub fn astar<N, C, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+    where N: Hash + Eq + Clone, C: Ord + Copy + Default + Add<Output=C>, I: IntoIterator<Item=(N, C)> {
+    let mut best = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut closed = HashSet::new();
+    let mut heap = BinaryHeap::new();
+    let mut nodes = vec![start.clone()];
+    best.insert(start.clone(), C::default());
+    heap.push(Reverse((heuristic(&start), C::default(), 0)));
+
+    while let Some(Reverse((_, cost, index))) = heap.pop() {
+        let node = nodes[index].clone();
+        if !closed.insert(node.clone()) || best[&node] < cost {
+            continue;
+        }
+        if is_goal(&node) {
+            return Some((build_path(&parents, &node), cost));
+        }
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if !closed.contains(&next) && best.get(&next).is_none_or(|&c| next_cost < c) {
+                best.insert(next.clone(), next_cost);
+                parents.insert(next.clone(), node.clone());
+                heap.push(Reverse((next_cost + heuristic(&next), next_cost, nodes.len())));
+                nodes.push(next);
+            }
+        }
+    }
+    None
+}
+
+// Kahn's algorithm that always takes the smallest available node,
+// so the order is the lexicographically smallest one
+pub fn topological_sort<N: Hash + Eq + Ord + Clone>(
+    nodes: impl IntoIterator<Item=N>,
+    edges: impl IntoIterator<Item=(N, N)>,
+) -> Result<Vec<N>, GraphError<N>> {
+    let mut in_degrees: HashMap<N, usize> = nodes.into_iter().map(|node| (node, 0)).collect();
+    let mut successors: HashMap<N, Vec<N>> = HashMap::new();
+    for (from, to) in edges {
+        in_degrees.entry(from.clone()).or_insert(0);
+        *in_degrees.entry(to.clone()).or_insert(0) += 1;
+        successors.entry(from).or_default().push(to);
+    }
+
+    let mut available: BinaryHeap<Reverse<N>> = in_degrees.iter()
+        .filter(|(_, degree)| **degree == 0)
+        .map(|(node, _)| Reverse(node.clone()))
+        .collect();
+    let mut result = Vec::with_capacity(in_degrees.len());
+    while let Some(Reverse(node)) = available.pop() {
+        for next in successors.get(&node).into_iter().flatten() {
+            let degree = in_degrees.get_mut(next).unwrap();
+            *degree -= 1;
+            if *degree == 0 {
+                available.push(Reverse(next.clone()));
+            }
+        }
+        result.push(node);
+    }
+
+    if result.len() < in_degrees.len() {
+        let mut remaining: Vec<N> = in_degrees.into_iter()
+            .filter(|(_, degree)| *degree > 0)
+            .map(|(node, _)| node)
+            .collect();
+        remaining.sort();
+        return Err(CycleFound(remaining));
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphError<N> {
+    CycleFound(Vec<N>)
+}
+
+impl<N: Debug> Display for GraphError<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CycleFound(nodes) => { write!(f, "Nodes {:?} are part of a cycle", nodes) }
+        }
+    }
+}
+
+impl<N: Debug> error::Error for GraphError<N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{DenseGrid, Grid, Point};
+    use pretty_assertions::assert_eq;
+
+    const MAZE: &str = "\
+#######
+#E..G.#
+#...#.#
+#.G.#G#
+#######";
+
+    fn parse_maze() -> DenseGrid<char> {
+        DenseGrid::parse(MAZE, Some).unwrap()
+    }
+
+    fn open_neighbours(maze: &DenseGrid<char>) -> impl Fn(&Point) -> Vec<Point> + '_ {
+        move |point| maze.neighbours4(*point)
+            .filter(|(_, chr)| **chr == '.')
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    fn next_to_goblin(maze: &DenseGrid<char>) -> impl Fn(&Point) -> bool + '_ {
+        move |point| maze.neighbours4(*point).any(|(_, chr)| *chr == 'G')
+    }
+
+    #[test]
+    fn bfs_reading_order_test() {
+        let maze = parse_maze();
+        let start = Point::new(1, 1);
+        let result = bfs(start, open_neighbours(&maze), next_to_goblin(&maze)).unwrap();
+
+        // (3, 1), (2, 2) and (1, 3) are all in range two steps away,
+        // (3, 1) comes first in reading order and is reached through (2, 1)
+        assert_eq!(result, [start, Point::new(2, 1), Point::new(3, 1)]);
+    }
+
+    #[test]
+    fn bfs_first_step_test() {
+        // Both (2, 1) and (1, 2) lead to (2, 2) in two steps; (2, 1) is first in reading order
+        let maze = DenseGrid::parse("\
+#####
+#...#
+#...#
+#####", Some).unwrap();
+        let goal = Point::new(2, 2);
+        let result = bfs(Point::new(1, 1), open_neighbours(&maze), |p| *p == goal).unwrap();
+        assert_eq!(result, [Point::new(1, 1), Point::new(2, 1), goal]);
+    }
+
+    #[test]
+    fn bfs_unreachable_test() {
+        let maze = parse_maze();
+        let result = bfs(Point::new(1, 1), open_neighbours(&maze), |p| *p == Point::new(5, 1));
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn bfs_distances_test() {
+        let maze = parse_maze();
+        let result = bfs_distances(Point::new(1, 1), open_neighbours(&maze));
+        assert_eq!(result.len(), 8);
+        assert_eq!(result[&Point::new(3, 3)], 4);
+    }
+
+    //   A --1-- B --2-- D
+    //   |       |       |
+    //   4       1       1
+    //   |       |       |
+    //   C --1-- E --5-- F
+    fn weighted(node: &char) -> Vec<(char, u32)> {
+        let edges = [('A', 'B', 1), ('A', 'C', 4), ('B', 'D', 2), ('B', 'E', 1),
+            ('C', 'E', 1), ('D', 'F', 1), ('E', 'F', 5)];
+        edges.iter().filter_map(|&(a, b, cost)| {
+            if a == *node { Some((b, cost)) } else if b == *node { Some((a, cost)) } else { None }
+        }).collect()
+    }
+
+    #[test]
+    fn dijkstra_test() {
+        let result = dijkstra('A', weighted, |node| *node == 'F');
+        assert_eq!(result, Some((vec!['A', 'B', 'D', 'F'], 4)));
+
+        let result = dijkstra('A', weighted, |node| *node == 'C');
+        assert_eq!(result, Some((vec!['A', 'B', 'E', 'C'], 3)));
+
+        let result = dijkstra('A', weighted, |node| *node == 'Z');
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn dijkstra_all_test() {
+        let result = dijkstra_all('A', weighted);
+        assert_eq!(result.len(), 6);
+        assert_eq!(result[&'A'], (0, None));
+        assert_eq!(result[&'F'], (4, Some('D')));
+
+        let parents: HashMap<char, char> = result.iter()
+            .filter_map(|(node, (_, parent))| parent.map(|parent| (*node, parent)))
+            .collect();
+        assert_eq!(build_path(&parents, &'C'), ['A', 'B', 'E', 'C']);
+    }
+
+    #[test]
+    fn astar_matches_dijkstra_test() {
+        let open = DenseGrid::parse("\
+..........
+.#######..
+.......#..
+######.#..
+.......#..
+.####.....", |chr| Some(chr == '.')).unwrap();
+        let start = Point::new(0, 5);
+        let goal = Point::new(9, 0);
+        let neighbours = |point: &Point| -> Vec<(Point, u64)> {
+            open.neighbours4(*point).filter(|(_, open)| **open).map(|(p, _)| (p, 1)).collect()
+        };
+
+        let expected = dijkstra(start, neighbours, |p| *p == goal).unwrap();
+        let result = astar(start, neighbours, |p| p.manhattan(&goal), |p| *p == goal).unwrap();
+        assert_eq!(result.1, expected.1);
+        assert_eq!(result.0.len(), expected.0.len());
+        assert_eq!(result.0.first(), Some(&start));
+        assert_eq!(result.0.last(), Some(&goal));
+    }
+
+    #[test]
+    fn topological_sort_test() {
+        let edges = vec![('C', 'A'), ('C', 'F'), ('A', 'B'), ('A', 'D'), ('B', 'E'), ('D', 'E'), ('F', 'E')];
+        let result: String = topological_sort(vec![], edges).unwrap().into_iter().collect();
+        assert_eq!(result, "CABDFE");
+    }
+
+    #[test]
+    fn topological_sort_isolated_nodes_test() {
+        let result = topological_sort(vec![3, 1, 2], vec![(3, 0)]).unwrap();
+        assert_eq!(result, [1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn topological_sort_cycle_test() {
+        let edges = vec![('A', 'B'), ('B', 'C'), ('C', 'B'), ('C', 'D')];
+        let result = topological_sort(vec![], edges).unwrap_err();
+        assert_eq!(result, CycleFound(vec!['B', 'C', 'D']));
+    }
+}
@@ -0,0 +1,669 @@
+use std::{
+    cmp::{max, min, Ordering},
+    collections::HashMap,
+    error,
+    fmt::{Display, Formatter},
+    ops::{Add, Index, IndexMut, Sub},
+};
+use crate::interval::Interval;
+use GridError::{EmptyGrid, RaggedRow, UnknownCell};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan(&self, other: &Point) -> u64 {
+        (self.x - other.x).unsigned_abs() + (self.y - other.y).unsigned_abs()
+    }
+
+    // Up, left, right, down: reading order of the neighbours
+    pub fn neighbours4(self) -> impl Iterator<Item=Point> {
+        [(0, -1), (-1, 0), (1, 0), (0, 1)].iter()
+            .map(move |&(dx, dy)| self + Point::new(dx, dy))
+    }
+
+    pub fn neighbours8(self) -> impl Iterator<Item=Point> {
+        [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)].iter()
+            .map(move |&(dx, dy)| self + Point::new(dx, dy))
+    }
+}
+
+// Points are ordered in reading order: top to bottom, then left to right
+impl Ord for Point {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.y, self.x).cmp(&(other.y, other.x))
+    }
+}
+
+impl PartialOrd for Point {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+// Inclusive on both corners, so a single point has width and height of 1
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub const fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item=Point>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |bounds, point| bounds.include(point)))
+    }
+
+    pub fn width(&self) -> u64 {
+        (self.max.x - self.min.x + 1) as u64
+    }
+
+    pub fn height(&self) -> u64 {
+        (self.max.y - self.min.y + 1) as u64
+    }
+
+    pub fn area(&self) -> u64 {
+        self.width() * self.height()
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    pub fn x_interval(&self) -> Interval {
+        Interval::new(self.min.x, self.max.x + 1)
+    }
+
+    pub fn y_interval(&self) -> Interval {
+        Interval::new(self.min.y, self.max.y + 1)
+    }
+
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.x_interval().overlaps(&other.x_interval()) && self.y_interval().overlaps(&other.y_interval())
+    }
+
+    pub fn intersection(&self, other: &Bounds) -> Option<Bounds> {
+        let x = self.x_interval().intersection(&other.x_interval())?;
+        let y = self.y_interval().intersection(&other.y_interval())?;
+        Some(Self::new(Point::new(x.start, y.start), Point::new(x.end - 1, y.end - 1)))
+    }
+
+    pub fn include(&self, point: Point) -> Bounds {
+        Self::new(
+            Point::new(min(self.min.x, point.x), min(self.min.y, point.y)),
+            Point::new(max(self.max.x, point.x), max(self.max.y, point.y)),
+        )
+    }
+
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        self.include(other.min).include(other.max)
+    }
+
+    pub fn points(&self) -> impl Iterator<Item=Point> {
+        self.rows().flatten()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item=impl Iterator<Item=Point>> {
+        let (min_x, max_x) = (self.min.x, self.max.x);
+        (self.min.y..=self.max.y).map(move |y| (min_x..=max_x).map(move |x| Point::new(x, y)))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item=impl Iterator<Item=Point>> {
+        let (min_y, max_y) = (self.min.y, self.max.y);
+        (self.min.x..=self.max.x).map(move |x| (min_y..=max_y).map(move |y| Point::new(x, y)))
+    }
+}
+
+// Area covered by at least `depth` of the rectangles. A line sweeps across x and stops only
+// where a rectangle starts or ends, so the area is never split into squares; the y intervals
+// crossing the line are kept in a segment tree, so each stop takes O(depth log n)
+pub fn covered_area(rectangles: &[Bounds], depth: usize) -> u128 {
+    let mut events: Vec<(i64, isize, &Bounds)> = rectangles.iter()
+        .flat_map(|rectangle| vec![(rectangle.min.x, 1, rectangle), (rectangle.max.x + 1, -1, rectangle)])
+        .collect();
+    events.sort_unstable_by_key(|&(x, delta, _)| (x, delta));
+    let mut ys: Vec<i64> = rectangles.iter()
+        .flat_map(|rectangle| vec![rectangle.min.y, rectangle.max.y + 1])
+        .collect();
+    ys.sort_unstable();
+    ys.dedup();
+    let mut tree = CoverTree::new(ys, depth.max(1));
+
+    let mut area = 0;
+    let mut last_x = None;
+    for (x, delta, rectangle) in events {
+        if let Some(last_x) = last_x {
+            area += (x as i128 - last_x as i128) as u128 * tree.covered() as u128;
+        }
+        tree.add(rectangle.y_interval(), delta);
+        last_x = Some(x);
+    }
+    area
+}
+
+// Segment tree over the pieces between consecutive `ys`. An interval counts in the nodes
+// that exactly cover it; `lengths[node][c]` is how much of the node is covered at least
+// `c + 1` times by the intervals counted in the node or below it
+struct CoverTree {
+    ys: Vec<i64>,
+    depth: usize,
+    counts: Vec<usize>,
+    lengths: Vec<Vec<u64>>,
+}
+
+impl CoverTree {
+    fn new(ys: Vec<i64>, depth: usize) -> Self {
+        let nodes = 4 * ys.len().saturating_sub(1).max(1);
+        Self { ys, depth, counts: vec![0; nodes], lengths: vec![vec![0; depth]; nodes] }
+    }
+
+    fn pieces(&self) -> usize {
+        self.ys.len().saturating_sub(1).max(1)
+    }
+
+    // Length covered at least `depth` times
+    fn covered(&self) -> u64 {
+        self.lengths[1][self.depth - 1]
+    }
+
+    // `interval` has to start and end at one of the `ys`
+    fn add(&mut self, interval: Interval, delta: isize) {
+        let from = self.ys.binary_search(&interval.start).unwrap();
+        let to = self.ys.binary_search(&interval.end).unwrap();
+        self.add_node(1, (0, self.pieces()), (from, to), delta);
+    }
+
+    fn add_node(&mut self, node: usize, (low, high): (usize, usize), (from, to): (usize, usize), delta: isize) {
+        if to <= low || high <= from {
+            return;
+        }
+        if from <= low && high <= to {
+            self.counts[node] = (self.counts[node] as isize + delta) as usize;
+        } else {
+            let middle = (low + high) / 2;
+            self.add_node(2 * node, (low, middle), (from, to), delta);
+            self.add_node(2 * node + 1, (middle, high), (from, to), delta);
+        }
+
+        let length = (self.ys[high] - self.ys[low]) as u64;
+        let count = self.counts[node];
+        for c in 0..self.depth {
+            self.lengths[node][c] = if c < count {
+                length
+            } else if high - low == 1 {
+                0
+            } else {
+                self.lengths[2 * node][c - count] + self.lengths[2 * node + 1][c - count]
+            };
+        }
+    }
+}
+
+pub trait Grid<T> {
+    fn bounds(&self) -> Option<Bounds>;
+
+    fn get(&self, point: Point) -> Option<&T>;
+
+    fn neighbours4<'a>(&'a self, point: Point) -> impl Iterator<Item=(Point, &'a T)> where T: 'a {
+        point.neighbours4().filter_map(move |p| self.get(p).map(|value| (p, value)))
+    }
+
+    fn neighbours8<'a>(&'a self, point: Point) -> impl Iterator<Item=(Point, &'a T)> where T: 'a {
+        point.neighbours8().filter_map(move |p| self.get(p).map(|value| (p, value)))
+    }
+
+    fn render(&self, draw: impl Fn(Option<&T>) -> char) -> String {
+        let bounds = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+        bounds.rows()
+            .map(|row| row.map(|point| draw(self.get(point))).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DenseGrid<T> {
+    bounds: Bounds,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> DenseGrid<T> {
+    pub fn new(bounds: Bounds, fill: T) -> Self {
+        Self { bounds, cells: vec![fill; bounds.area() as usize] }
+    }
+}
+
+impl<T> DenseGrid<T> {
+    // Top-left character of the text is (0, 0); every row must have the same length
+    pub fn parse(text: &str, mut cell: impl FnMut(char) -> Option<T>) -> Result<Self, GridError> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for (y, line) in text.lines().enumerate() {
+            let mut row_width = 0;
+            for (x, chr) in line.chars().enumerate() {
+                let point = Point::new(x as i64, y as i64);
+                cells.push(cell(chr).ok_or(UnknownCell(point, chr))?);
+                row_width += 1;
+            }
+            match width {
+                None => width = Some(row_width),
+                Some(width) if width != row_width => {
+                    return Err(RaggedRow { row: y, expected: width, found: row_width });
+                }
+                Some(_) => {}
+            }
+            height += 1;
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Self {
+                bounds: Bounds::new(Point::new(0, 0), Point::new(width as i64 - 1, height - 1)),
+                cells,
+            }),
+            _ => Err(EmptyGrid),
+        }
+    }
+
+    fn index_of(&self, point: Point) -> Option<usize> {
+        if self.bounds.contains(point) {
+            let offset = point - self.bounds.min;
+            Some((offset.y as u64 * self.bounds.width() + offset.x as u64) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        let index = self.index_of(point)?;
+        Some(&mut self.cells[index])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        self.cells.chunks(self.bounds.width() as usize)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item=impl Iterator<Item=&T>> {
+        let width = self.bounds.width() as usize;
+        (0..width).map(move |x| self.cells.iter().skip(x).step_by(width))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(Point, &T)> {
+        self.bounds.points().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item=&T> {
+        self.cells.iter()
+    }
+}
+
+impl<T> Grid<T> for DenseGrid<T> {
+    fn bounds(&self) -> Option<Bounds> {
+        Some(self.bounds)
+    }
+
+    fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+}
+
+impl<T> Index<Point> for DenseGrid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point).expect("Point is out of grid bounds")
+    }
+}
+
+impl<T> IndexMut<Point> for DenseGrid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point).expect("Point is out of grid bounds")
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self { cells: HashMap::new() }
+    }
+
+    // Characters for which `cell` returns `None` are left empty
+    pub fn parse(text: &str, mut cell: impl FnMut(char) -> Option<T>) -> Self {
+        let mut grid = Self::new();
+        for (y, line) in text.lines().enumerate() {
+            for (x, chr) in line.chars().enumerate() {
+                if let Some(value) = cell(chr) {
+                    grid.insert(Point::new(x as i64, y as i64), value);
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn insert(&mut self, point: Point, value: T) -> Option<T> {
+        self.cells.insert(point, value)
+    }
+
+    pub fn remove(&mut self, point: Point) -> Option<T> {
+        self.cells.remove(&point)
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    pub fn get_or_insert_with(&mut self, point: Point, default: impl FnOnce() -> T) -> &mut T {
+        self.cells.entry(point).or_insert_with(default)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(Point, &T)> {
+        self.cells.iter().map(|(point, value)| (*point, value))
+    }
+}
+
+impl<T> Grid<T> for SparseGrid<T> {
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::from_points(self.cells.keys().copied())
+    }
+
+    fn get(&self, point: Point) -> Option<&T> {
+        self.cells.get(&point)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GridError {
+    EmptyGrid,
+    RaggedRow { row: usize, expected: usize, found: usize },
+    UnknownCell(Point, char),
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmptyGrid => { write!(f, "Grid has no cells") }
+            RaggedRow { row, expected, found } => {
+                write!(f, "Row {} has {} cells, expected {}", row, found, expected)
+            }
+            UnknownCell(point, chr) => {
+                write!(f, "Unknown cell '{}' at {},{}", chr, point.x, point.y)
+            }
+        }
+    }
+}
+
+impl error::Error for GridError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use test_case::test_case;
+    use pretty_assertions::assert_eq;
+
+    const BOUNDS: Bounds = Bounds::new(Point::new(1, 3), Point::new(4, 6));
+
+    #[test_case(Point::new(0, 0), Point::new(0, 0), 0)]
+    #[test_case(Point::new(1, 2), Point::new(4, 6), 7)]
+    #[test_case(Point::new(-3, 2), Point::new(3, -2), 10)]
+    fn manhattan_test(a: Point, b: Point, expected: u64) {
+        pretty_assertions::assert_eq!(a.manhattan(&b), expected);
+    }
+
+    #[test]
+    fn reading_order_test() {
+        let mut points = vec![Point::new(2, 1), Point::new(0, 2), Point::new(1, 1), Point::new(5, 0)];
+        points.sort();
+        assert_eq!(points, [Point::new(5, 0), Point::new(1, 1), Point::new(2, 1), Point::new(0, 2)]);
+    }
+
+    #[test]
+    fn neighbours4_test() {
+        let result: Vec<_> = Point::new(1, 1).neighbours4().collect();
+        assert_eq!(result, [Point::new(1, 0), Point::new(0, 1), Point::new(2, 1), Point::new(1, 2)]);
+    }
+
+    #[test]
+    fn neighbours8_test() {
+        let result: Vec<_> = Point::new(0, 0).neighbours8().collect();
+        assert_eq!(result.len(), 8);
+        assert!(result.iter().all(|p| p.manhattan(&Point::new(0, 0)) <= 2));
+        assert!(!result.contains(&Point::new(0, 0)));
+    }
+
+    #[test]
+    fn bounds_size_test() {
+        assert_eq!((BOUNDS.width(), BOUNDS.height(), BOUNDS.area()), (4, 4, 16));
+    }
+
+    #[test]
+    fn bounds_from_points_test() {
+        let result = Bounds::from_points(vec![Point::new(3, 5), Point::new(1, 6), Point::new(4, 3)]);
+        assert_eq!(result, Some(BOUNDS));
+        assert_eq!(Bounds::from_points(vec![]), None);
+    }
+
+    const INTERSECTION_SOME: (Bounds, Bounds) = (
+        Bounds::new(Point::new(1, 3), Point::new(4, 6)),
+        Bounds::new(Point::new(3, 1), Point::new(6, 4)),
+    );
+    const INTERSECTION_NONE: (Bounds, Bounds) = (
+        Bounds::new(Point::new(1, 3), Point::new(4, 6)),
+        Bounds::new(Point::new(5, 5), Point::new(6, 6)),
+    );
+
+    #[test_case(INTERSECTION_SOME, true)]
+    #[test_case(INTERSECTION_NONE, false)]
+    fn intersects_test((a, b): (Bounds, Bounds), result: bool) {
+        pretty_assertions::assert_eq!(a.intersects(&b), result);
+        pretty_assertions::assert_eq!(b.intersects(&a), result);
+    }
+
+    #[test]
+    fn intersection_test() {
+        let (a, b) = INTERSECTION_SOME;
+        let expected = Bounds::new(Point::new(3, 3), Point::new(4, 4));
+        assert_eq!(a.intersection(&b), Some(expected));
+
+        let (a, b) = INTERSECTION_NONE;
+        assert_eq!(a.intersection(&b), None);
+    }
+
+    #[test]
+    fn covered_area_test() {
+        let rectangles = [
+            Bounds::new(Point::new(1, 3), Point::new(4, 6)),
+            Bounds::new(Point::new(3, 1), Point::new(6, 4)),
+            Bounds::new(Point::new(5, 5), Point::new(6, 6)),
+            Bounds::new(Point::new(2, 2), Point::new(3, 3)),
+        ];
+        assert_eq!(covered_area(&rectangles, 1), 33);
+        assert_eq!(covered_area(&rectangles, 2), 6);
+        assert_eq!(covered_area(&rectangles, 3), 1);
+        assert_eq!(covered_area(&rectangles, 4), 0);
+        assert_eq!(covered_area(&[], 1), 0);
+    }
+
+    #[test]
+    fn covered_area_matches_squares_test() {
+        let mut generator = Random::new(3);
+        let mut random = move |bound: u64| generator.below(bound) as i64;
+        for _ in 0..200 {
+            let rectangles: Vec<Bounds> = (0..random(12))
+                .map(|_| {
+                    let min = Point::new(random(20) - 5, random(20) - 5);
+                    Bounds::new(min, min + Point::new(random(8), random(8)))
+                })
+                .collect();
+            let mut counts = HashMap::new();
+            for square in rectangles.iter().flat_map(Bounds::points) {
+                *counts.entry(square).or_insert(0) += 1;
+            }
+            for depth in 1..=3 {
+                let expected = counts.values().filter(|&&count| count >= depth).count() as u128;
+                assert_eq!(covered_area(&rectangles, depth), expected, "{:?}", rectangles);
+            }
+        }
+    }
+
+    // Claims of 2 by 2 at every point of a 200 by 200 lattice, so all but the
+    // corner squares are under two or more claims
+    #[test]
+    fn covered_area_many_test() {
+        let rectangles: Vec<Bounds> = (0..200)
+            .flat_map(|y| (0..200).map(move |x| Bounds::new(Point::new(x, y), Point::new(x + 1, y + 1))))
+            .collect();
+        assert_eq!(covered_area(&rectangles, 2), 201 * 201 - 4);
+        assert_eq!(covered_area(&rectangles, 4), 199 * 199);
+        assert_eq!(covered_area(&rectangles, 5), 0);
+    }
+
+    #[test]
+    fn covered_area_large_test() {
+        let side = 1 << 40;
+        let rectangles = [
+            Bounds::new(Point::new(0, 0), Point::new(side - 1, side - 1)),
+            Bounds::new(Point::new(side / 2, -side), Point::new(2 * side, side / 2 - 1)),
+        ];
+        let area = |bounds: &Bounds| bounds.width() as u128 * bounds.height() as u128;
+        let (a, b) = (area(&rectangles[0]), area(&rectangles[1]));
+        let overlap = (side as u128 / 2).pow(2);
+        assert_eq!(covered_area(&rectangles, 2), overlap);
+        assert_eq!(covered_area(&rectangles, 1), a + b - overlap);
+    }
+
+    #[test]
+    fn bounds_rows_and_columns_test() {
+        let bounds = Bounds::new(Point::new(0, 0), Point::new(1, 2));
+        let rows: Vec<Vec<_>> = bounds.rows().map(|row| row.collect()).collect();
+        let columns: Vec<Vec<_>> = bounds.columns().map(|column| column.collect()).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1], [Point::new(0, 1), Point::new(1, 1)]);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[1], [Point::new(1, 0), Point::new(1, 1), Point::new(1, 2)]);
+    }
+
+    const MAP: &str = "\
+#..#
+.##.
+#...";
+
+    fn parse_map() -> DenseGrid<bool> {
+        DenseGrid::parse(MAP, |chr| match chr {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }).unwrap()
+    }
+
+    #[test]
+    fn dense_parse_and_render_test() {
+        let grid = parse_map();
+        assert_eq!(grid.bounds(), Some(Bounds::new(Point::new(0, 0), Point::new(3, 2))));
+        assert_eq!(grid[Point::new(1, 1)], true);
+        assert_eq!(grid.get(Point::new(4, 0)), None);
+
+        let result = grid.render(|cell| if *cell.unwrap() { '#' } else { '.' });
+        assert_eq!(result, MAP);
+    }
+
+    #[test_case("", EmptyGrid)]
+    #[test_case("##\n#", RaggedRow { row: 1, expected: 2, found: 1 })]
+    #[test_case("#.\n?#", UnknownCell(Point::new(0, 1), '?'))]
+    fn dense_parse_error_test(input: &str, expected: GridError) {
+        let result = DenseGrid::parse(input, |chr| match chr {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }).unwrap_err();
+        pretty_assertions::assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn dense_rows_and_columns_test() {
+        let grid = parse_map();
+        let rows: Vec<_> = grid.rows().collect();
+        assert_eq!(rows[1], [false, true, true, false]);
+
+        let first_column: Vec<_> = grid.columns().next().unwrap().copied().collect();
+        assert_eq!(first_column, [true, false, true]);
+    }
+
+    #[test]
+    fn dense_offset_bounds_test() {
+        let mut grid = DenseGrid::new(BOUNDS, 0);
+        grid[Point::new(4, 6)] += 5;
+        grid[Point::new(1, 3)] += 1;
+        assert_eq!(grid.values().sum::<i32>(), 6);
+        assert_eq!(grid.iter().last(), Some((Point::new(4, 6), &5)));
+        assert_eq!(grid.get(Point::new(0, 3)), None);
+    }
+
+    #[test]
+    fn neighbours_in_grid_test() {
+        let grid = parse_map();
+        let result: Vec<_> = grid.neighbours4(Point::new(0, 0)).map(|(p, _)| p).collect();
+        assert_eq!(result, [Point::new(1, 0), Point::new(0, 1)]);
+
+        let walls = grid.neighbours8(Point::new(1, 1)).filter(|(_, wall)| **wall).count();
+        assert_eq!(walls, 3);
+    }
+
+    #[test]
+    fn sparse_grid_test() {
+        let mut grid = SparseGrid::parse(MAP, |chr| if chr == '#' { Some(1) } else { None });
+        assert_eq!(grid.len(), 5);
+        assert_eq!(grid.bounds(), Some(Bounds::new(Point::new(0, 0), Point::new(3, 2))));
+
+        *grid.get_or_insert_with(Point::new(-1, 0), || 0) += 2;
+        assert_eq!(grid.get(Point::new(-1, 0)), Some(&2));
+
+        let result = grid.render(|cell| cell.map_or('.', |_| '#'));
+        assert_eq!(result, "\
+##..#
+..##.
+.#...");
+    }
+}
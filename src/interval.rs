@@ -0,0 +1,257 @@
+use std::{
+    cmp::{max, min},
+    iter::FromIterator,
+    ops::Range,
+};
+
+// Half-open, like `Range`: `start` is covered and `end` is not
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub const fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> u64 {
+        if self.is_empty() { 0 } else { (self.end - self.start) as u64 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(&self, point: i64) -> bool {
+        self.start <= point && point < self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    // Adjacent intervals touch without overlapping, so they can be merged
+    pub fn touches(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        if self.overlaps(other) {
+            Some(Self::new(max(self.start, other.start), min(self.end, other.end)))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Range<i64>> for Interval {
+    fn from(range: Range<i64>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+// Sorted, non-empty intervals, with overlapping and adjacent ones merged together
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self { intervals: Vec::new() }
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() { return; }
+
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+        let merged = self.intervals[first..last].iter().fold(interval, |acc, i| {
+            Interval::new(min(acc.start, i.start), max(acc.end, i.end))
+        });
+        self.intervals.splice(first..last, Some(merged));
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = self.clone();
+        for interval in &other.intervals {
+            result.insert(*interval);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            if let Some(intersection) = a.intersection(&b) {
+                intervals.push(intersection);
+            }
+            if a.end < b.end { i += 1 } else { j += 1 }
+        }
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let mut others = other.intervals.iter().peekable();
+        for interval in &self.intervals {
+            let mut start = interval.start;
+            while let Some(cut) = others.peek() {
+                if cut.end <= start {
+                    others.next();
+                    continue;
+                }
+                if cut.start >= interval.end { break; }
+                if cut.start > start {
+                    intervals.push(Interval::new(start, cut.start));
+                }
+                start = cut.end;
+                if cut.end > interval.end { break; }
+                others.next();
+            }
+            if start < interval.end {
+                intervals.push(Interval::new(start, interval.end));
+            }
+        }
+        Self { intervals }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn contains(&self, point: i64) -> bool {
+        let index = self.intervals.partition_point(|i| i.end <= point);
+        self.intervals.get(index).is_some_and(|i| i.contains(point))
+    }
+
+    pub fn intervals(&self) -> impl Iterator<Item=&Interval> {
+        self.intervals.iter()
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item=Interval>>(iter: I) -> Self {
+        let mut result = Self::new();
+        for interval in iter {
+            result.insert(interval);
+        }
+        result
+    }
+}
+
+// Splits the line into pieces covered by the same number of intervals;
+// pieces covered by none of them are left out
+pub fn coverage(intervals: impl IntoIterator<Item=Interval>) -> Vec<(Interval, usize)> {
+    let mut events: Vec<(i64, isize)> = intervals.into_iter()
+        .filter(|i| !i.is_empty())
+        .flat_map(|i| vec![(i.start, 1), (i.end, -1)])
+        .collect();
+    events.sort_unstable();
+
+    let mut result = Vec::new();
+    let mut count = 0isize;
+    let mut events = events.into_iter().peekable();
+    while let Some((point, delta)) = events.next() {
+        count += delta;
+        while let Some(&(next_point, next_delta)) = events.peek() {
+            if next_point != point { break; }
+            count += next_delta;
+            events.next();
+        }
+        if let Some(&(next_point, _)) = events.peek() {
+            if count > 0 {
+                result.push((Interval::new(point, next_point), count as usize));
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+    use pretty_assertions::assert_eq;
+
+    fn set(ranges: &[(i64, i64)]) -> IntervalSet {
+        ranges.iter().map(|&(start, end)| Interval::new(start, end)).collect()
+    }
+
+    #[test_case(0..5, 3..8, Some(3..5))]
+    #[test_case(0..5, 5..8, None)]
+    #[test_case(2..3, 0..8, Some(2..3))]
+    fn intersection_test(a: Range<i64>, b: Range<i64>, expected: Option<Range<i64>>) {
+        let result = Interval::from(a).intersection(&Interval::from(b));
+        pretty_assertions::assert_eq!(result, expected.map(Interval::from));
+    }
+
+    #[test]
+    fn insert_merges_overlapping_and_adjacent_test() {
+        let result = set(&[(5, 7), (0, 2), (10, 12), (2, 3), (6, 9), (4, 4)]);
+        assert_eq!(result, set(&[(0, 3), (5, 9), (10, 12)]));
+        assert_eq!(result.len(), 9);
+    }
+
+    #[test]
+    fn insert_bridging_test() {
+        let mut result = set(&[(0, 2), (4, 6), (8, 10)]);
+        result.insert(Interval::new(1, 9));
+        assert_eq!(result, set(&[(0, 10)]));
+    }
+
+    #[test]
+    fn union_test() {
+        let result = set(&[(0, 3), (10, 12)]).union(&set(&[(2, 5), (12, 13), (20, 21)]));
+        assert_eq!(result, set(&[(0, 5), (10, 13), (20, 21)]));
+    }
+
+    #[test]
+    fn set_intersection_test() {
+        let result = set(&[(0, 5), (8, 12), (20, 30)]).intersection(&set(&[(3, 10), (11, 25)]));
+        assert_eq!(result, set(&[(3, 5), (8, 10), (11, 12), (20, 25)]));
+    }
+
+    #[test_case(&[(0, 10)], &[(3, 5)], &[(0, 3), (5, 10)])]
+    #[test_case(&[(0, 10)], &[(-5, 3), (8, 20)], &[(3, 8)])]
+    #[test_case(&[(0, 4), (6, 10)], &[(2, 8)], &[(0, 2), (8, 10)])]
+    #[test_case(&[(0, 4), (6, 10)], &[], &[(0, 4), (6, 10)])]
+    #[test_case(&[(0, 4)], &[(0, 4)], &[])]
+    fn difference_test(a: &[(i64, i64)], b: &[(i64, i64)], expected: &[(i64, i64)]) {
+        pretty_assertions::assert_eq!(set(a).difference(&set(b)), set(expected));
+    }
+
+    #[test_case(-1, false)]
+    #[test_case(0, true)]
+    #[test_case(2, false)]
+    #[test_case(6, true)]
+    #[test_case(8, false)]
+    fn contains_test(point: i64, expected: bool) {
+        pretty_assertions::assert_eq!(set(&[(0, 2), (5, 8)]).contains(point), expected);
+    }
+
+    #[test]
+    fn coverage_test() {
+        let result = coverage(vec![
+            Interval::new(5, 25),
+            Interval::new(30, 55),
+            Interval::new(24, 29),
+            Interval::new(0, 0),
+        ]);
+        let expected = vec![
+            (Interval::new(5, 24), 1),
+            (Interval::new(24, 25), 2),
+            (Interval::new(25, 29), 1),
+            (Interval::new(30, 55), 1),
+        ];
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,522 @@
+mod cluster;
+mod edit;
+mod neighbours;
+mod units;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+pub use cluster::{clusters, Cluster, ClusterReport, WILDCARD};
+pub use edit::{closest_pairs, damerau, format_script, levenshtein, Edit, EditPair, Metric, MetricError};
+pub use neighbours::BkTree;
+pub use units::{Normalization, Symbols, Units, UnitsError};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Combine {
+    Product,
+    Sum,
+    Table,
+}
+
+impl FromStr for Combine {
+    type Err = ChecksumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "product" => Ok(Combine::Product),
+            "sum" => Ok(Combine::Sum),
+            "table" => Ok(Combine::Table),
+            _ => Err(ChecksumError::UnknownCombine(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChecksumValue {
+    Number(u64),
+    Table(BTreeMap<usize, usize>),
+}
+
+impl Display for ChecksumValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChecksumValue::Number(number) => write!(f, "{}", number),
+            ChecksumValue::Table(table) => {
+                let rows: Vec<String> = table.iter().map(|(k, count)| format!("{}: {}", k, count)).collect();
+                write!(f, "{}", rows.join("\n"))
+            }
+        }
+    }
+}
+
+// `ids[k]` lists, in input order, the IDs with some letter appearing exactly `k` times
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checksum {
+    pub ids: BTreeMap<usize, Vec<String>>,
+    pub value: ChecksumValue,
+}
+
+impl Checksum {
+    pub fn count(&self, multiplicity: usize) -> usize {
+        self.ids.get(&multiplicity).map_or(0, Vec::len)
+    }
+}
+
+pub fn letter_counts(id: &str) -> HashMap<char, usize> {
+    id.chars().fold(HashMap::new(), |mut counts, letter| {
+        *counts.entry(letter).or_default() += 1;
+        counts
+    })
+}
+
+// How often each letter of an ID appears. IDs made of ASCII lowercase letters only,
+// like the puzzle's, are counted in an array; anything else falls back to a map
+#[derive(Debug, Clone, PartialEq)]
+pub enum Multiplicities {
+    Ascii([u32; 26]),
+    General(HashMap<char, usize>),
+}
+
+impl Multiplicities {
+    pub fn new(id: &str) -> Self {
+        let mut counts = [0; 26];
+        for byte in id.bytes() {
+            if !byte.is_ascii_lowercase() {
+                return Self::general(id);
+            }
+            counts[(byte - b'a') as usize] += 1;
+        }
+        Multiplicities::Ascii(counts)
+    }
+
+    pub fn general(id: &str) -> Self {
+        Multiplicities::General(letter_counts(id))
+    }
+
+    pub fn with_units(id: &str, units: Units) -> Self {
+        if units.is_chars() {
+            return Self::new(id);
+        }
+        Self::general(&Symbols::default().encode(units.split(id)))
+    }
+
+    // Whether some letter appears exactly `k` times
+    pub fn contains(&self, k: usize) -> bool {
+        match self {
+            Multiplicities::Ascii(counts) => k > 0 && counts.iter().any(|&count| count as usize == k),
+            Multiplicities::General(counts) => counts.values().any(|&count| count == k),
+        }
+    }
+}
+
+// Day 2a is `checksum(ids, &[2, 3], Combine::Product)`
+pub fn checksum(ids: impl Iterator<Item=String>, multiplicities: &[usize], combine: Combine) -> Result<Checksum, ChecksumError> {
+    checksum_with_units(ids, multiplicities, combine, Units::default())
+}
+
+pub fn checksum_with_units(
+    ids: impl Iterator<Item=String>,
+    multiplicities: &[usize],
+    combine: Combine,
+    units: Units,
+) -> Result<Checksum, ChecksumError> {
+    let mut lists: BTreeMap<usize, Vec<String>> = multiplicities.iter().map(|&k| (k, Vec::new())).collect();
+    for id in ids {
+        let present = Multiplicities::with_units(&id, units);
+        for (&k, list) in lists.iter_mut() {
+            if present.contains(k) {
+                list.push(id.clone());
+            }
+        }
+    }
+
+    let counts = lists.iter().map(|(&k, list)| (k, list.len()));
+    let value = match combine {
+        Combine::Product => counts.map(|(_, count)| count as u64)
+            .try_fold(1u64, |product, count| product.checked_mul(count))
+            .map(ChecksumValue::Number)
+            .ok_or(ChecksumError::Overflow)?,
+        Combine::Sum => ChecksumValue::Number(counts.map(|(_, count)| count as u64).sum()),
+        Combine::Table => ChecksumValue::Table(counts.collect()),
+    };
+    Ok(Checksum { ids: lists, value })
+}
+
+// Finds the first ID, in input order, that differs from an earlier one in exactly one position,
+// as `(earlier, later, position)` counted in characters. With several earlier IDs the first one wins.
+pub fn one_apart(ids: &[String]) -> Option<(usize, usize, usize)> {
+    let ids: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+    let first = one_apart_matches(&ids).find_map(|matches| matches.into_iter().min());
+    first
+}
+
+// Every pair of IDs one position apart, sorted
+pub fn all_one_apart(ids: &[String]) -> Vec<(usize, usize, usize)> {
+    let ids: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+    let mut pairs: Vec<_> = one_apart_matches(&ids).flatten().collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+// For every ID in order, the earlier IDs it is one position apart from.
+// Each ID is hashed once with every position masked out, using `hash - letter * BASE^k`,
+// so candidates are found in O(n·L) expected time and only checked letter by letter on a hit.
+fn one_apart_matches(ids: &[Vec<char>]) -> impl Iterator<Item=Vec<(usize, usize, usize)>> + '_ {
+    const BASE: u64 = 0x100000001b3;
+    let mut masked: HashMap<(usize, usize, u64), Vec<usize>> = HashMap::new();
+    let mut powers = vec![1u64];
+
+    ids.iter().enumerate().map(move |(later, id)| {
+        while powers.len() < id.len() {
+            powers.push(powers.last().unwrap().wrapping_mul(BASE));
+        }
+        let letter = |i: usize| id[i] as u64 + 1;
+        let hash = (0..id.len()).fold(0u64, |hash, i| hash.wrapping_mul(BASE).wrapping_add(letter(i)));
+        let mut matches = Vec::new();
+        for position in 0..id.len() {
+            let key = (id.len(), position, hash.wrapping_sub(letter(position).wrapping_mul(powers[id.len() - 1 - position])));
+            let earlier = masked.entry(key).or_default();
+            matches.extend(earlier.iter()
+                .filter(|&&earlier| differs_only_at(&ids[earlier], id, position))
+                .map(|&earlier| (earlier, later, position)));
+            earlier.push(later);
+        }
+        matches
+    })
+}
+
+// Number of positions where the IDs differ, letters past the end of the shorter ID counting as different
+pub fn hamming(a: &[char], b: &[char]) -> usize {
+    let differences = a.iter().zip(b).filter(|(x, y)| x != y).count();
+    differences + a.len().max(b.len()) - a.len().min(b.len())
+}
+
+fn differs_only_at(a: &[char], b: &[char], position: usize) -> bool {
+    a.len() == b.len() && a[position] != b[position]
+        && a.iter().zip(b).enumerate().all(|(i, (x, y))| i == position || x == y)
+}
+
+// Lines are counted from 0 like everywhere else, but shown counted from 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct OneApart {
+    pub ids: (String, String),
+    pub lines: (usize, usize),
+    pub position: usize,
+    pub common: String,
+}
+
+// Day 2b: the only two IDs that differ in exactly one position, all IDs being the same length
+pub fn find_pair(ids: &[String]) -> Result<OneApart, PairError> {
+    let expected = ids.first().map_or(0, |id| id.chars().count());
+    if let Some((line, found)) = ids.iter().map(|id| id.chars().count()).enumerate().find(|&(_, found)| found != expected) {
+        return Err(PairError::UnequalLength { line, expected, found, units: Units::default() });
+    }
+
+    let pairs = all_one_apart(ids);
+    let (earlier, later, position) = match pairs.as_slice() {
+        [] => return Err(PairError::NoPair),
+        [pair] => *pair,
+        _ => return Err(PairError::MultiplePairs(pairs.iter().map(|&(earlier, later, _)| (earlier, later)).collect())),
+    };
+    let common = ids[later].chars().enumerate().filter(|&(i, _)| i != position).map(|(_, letter)| letter).collect();
+    Ok(OneApart {
+        ids: (ids[earlier].clone(), ids[later].clone()),
+        lines: (earlier, later),
+        position,
+        common,
+    })
+}
+
+// `find_pair` on the units of the IDs. The position counts units and the common part is normalized
+pub fn find_pair_with_units(ids: &[String], units: Units) -> Result<OneApart, PairError> {
+    if units.is_chars() {
+        return find_pair(ids);
+    }
+    let mut symbols = Symbols::default();
+    let encoded: Vec<String> = ids.iter().map(|id| symbols.encode(units.split(id))).collect();
+    let pair = find_pair(&encoded).map_err(|error| match error {
+        PairError::UnequalLength { line, expected, found, .. } => PairError::UnequalLength { line, expected, found, units },
+        error => error,
+    })?;
+    let (earlier, later) = pair.lines;
+    Ok(OneApart {
+        ids: (ids[earlier].clone(), ids[later].clone()),
+        common: symbols.decode(&pair.common),
+        ..pair
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PairError {
+    NoPair,
+    MultiplePairs(Vec<(usize, usize)>),
+    UnequalLength { line: usize, expected: usize, found: usize, units: Units },
+}
+
+impl Display for PairError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PairError::NoPair => write!(f, "No two IDs differ in exactly one position"),
+            PairError::MultiplePairs(pairs) => {
+                let pairs: Vec<String> = pairs.iter().map(|(a, b)| format!("{} and {}", a + 1, b + 1)).collect();
+                write!(f, "Several pairs of IDs differ in exactly one position: lines {}", pairs.join(", "))
+            }
+            PairError::UnequalLength { line, expected, found, units } => {
+                write!(f, "Line {}: ID has {} {}, expected {}", line + 1, found, units.name(), expected)
+            }
+        }
+    }
+}
+
+impl error::Error for PairError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChecksumError {
+    UnknownCombine(String),
+    Overflow,
+}
+
+impl Display for ChecksumError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChecksumError::UnknownCombine(combine) => {
+                write!(f, "Unknown combination '{}', expected product, sum or table", combine)
+            }
+            ChecksumError::Overflow => write!(f, "Checksum does not fit in 64 bits"),
+        }
+    }
+}
+
+impl error::Error for ChecksumError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use pretty_assertions::assert_eq;
+
+    // Kept as the reference for `one_apart`: compares every ID with all earlier ones
+    fn one_apart_by_comparison(ids: &[String]) -> Option<(usize, usize, usize)> {
+        let ids: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+        for (later, id) in ids.iter().enumerate() {
+            for (earlier, other) in ids[..later].iter().enumerate() {
+                if other.len() != id.len() {
+                    continue;
+                }
+                let mut differences = (0..id.len()).filter(|&i| id[i] != other[i]);
+                if let (Some(position), None) = (differences.next(), differences.next()) {
+                    return Some((earlier, later, position));
+                }
+            }
+        }
+        None
+    }
+
+    const INPUT: &str = "abcdef
+bababc
+abbcde
+abcccd
+aabcdd
+abcdee
+ababab";
+
+    fn ids() -> impl Iterator<Item=String> {
+        INPUT.lines().map(String::from)
+    }
+
+    #[test]
+    fn checksum_product_test() {
+        let result = checksum(ids(), &[2, 3], Combine::Product).unwrap();
+        assert_eq!(result.value, ChecksumValue::Number(12));
+        assert_eq!(result.ids[&2], vec!["bababc", "abbcde", "aabcdd", "abcdee"]);
+        assert_eq!(result.ids[&3], vec!["bababc", "abcccd", "ababab"]);
+    }
+
+    #[test]
+    fn checksum_sum_test() {
+        let result = checksum(ids(), &[1, 2, 3, 4], Combine::Sum).unwrap();
+        assert_eq!(result.value, ChecksumValue::Number(6 + 4 + 3));
+        assert_eq!(result.count(4), 0);
+        assert_eq!(result.count(5), 0);
+    }
+
+    #[test]
+    fn checksum_table_test() {
+        let result = checksum(ids(), &[3, 2], Combine::Table).unwrap();
+        let expected: BTreeMap<usize, usize> = vec![(2, 4), (3, 3)].into_iter().collect();
+        assert_eq!(result.value, ChecksumValue::Table(expected));
+        assert_eq!(result.value.to_string(), "2: 4\n3: 3");
+    }
+
+    #[test]
+    fn checksum_overflow_test() {
+        let ids = || std::iter::repeat_n("abbcccddddeeeee".to_string(), 10_000);
+        let result = checksum(ids(), &[1, 2, 3, 4], Combine::Product).unwrap();
+        assert_eq!(result.value, ChecksumValue::Number(10_000u64.pow(4)));
+        assert_eq!(checksum(ids(), &[1, 2, 3, 4, 5], Combine::Product), Err(ChecksumError::Overflow));
+    }
+
+    #[test]
+    fn multiplicities_test() {
+        assert!(matches!(Multiplicities::new("bababc"), Multiplicities::Ascii(_)));
+        assert!(matches!(Multiplicities::new("babAbc"), Multiplicities::General(_)));
+        assert!(matches!(Multiplicities::new("ba\u{e9}\u{e9}c"), Multiplicities::General(_)));
+        let ascii = Multiplicities::new("bababc");
+        assert_eq!((0..5).map(|k| ascii.contains(k)).collect::<Vec<_>>(), vec![false, true, true, true, false]);
+    }
+
+    #[test]
+    fn multiplicities_paths_agree_test() {
+        let mut generator = Random::new(2);
+        let mut random = move |bound: u64| generator.below(bound);
+        let alphabet: Vec<char> = "abcdez\u{e9}A".chars().collect();
+        for _ in 0..1000 {
+            let length = random(12) as usize;
+            let letters = if random(2) == 0 { 6 } else { alphabet.len() as u64 };
+            let id: String = (0..length).map(|_| alphabet[random(letters) as usize]).collect();
+            let (fast, general) = (Multiplicities::new(&id), Multiplicities::general(&id));
+            for k in 0..8 {
+                assert_eq!(fast.contains(k), general.contains(k), "{:?} {}", id, k);
+            }
+        }
+    }
+
+    const ONE_APART_INPUT: &str = "abcde
+fghij
+klmno
+pqrst
+fguij
+axcye
+wvxyz";
+
+    fn random_ids(seed: u64, count: usize, length: usize, letters: u64) -> Vec<String> {
+        let mut random = Random::new(seed);
+        (0..count).map(|_| {
+            (0..length).map(|_| (b'a' + random.below(letters) as u8) as char).collect()
+        }).collect()
+    }
+
+    #[test]
+    fn one_apart_test() {
+        let ids: Vec<String> = ONE_APART_INPUT.lines().map(String::from).collect();
+        assert_eq!(one_apart(&ids), Some((1, 4, 2)));
+        assert_eq!(one_apart(&ids[..4]), None);
+    }
+
+    #[test]
+    fn one_apart_skips_equal_and_different_lengths_test() {
+        let ids: Vec<String> = vec!["abc", "abc", "abcd", "abd", "xbd"].into_iter().map(String::from).collect();
+        assert_eq!(one_apart(&ids), Some((0, 3, 2)));
+        assert_eq!(one_apart(&ids[..3]), None);
+    }
+
+    #[test]
+    fn one_apart_unicode_test() {
+        let ids: Vec<String> = vec!["\u{e9}t\u{e9}", "\u{e9}t\u{e8}"].into_iter().map(String::from).collect();
+        assert_eq!(one_apart(&ids), Some((0, 1, 2)));
+    }
+
+    #[test]
+    fn one_apart_matches_comparison_test() {
+        for seed in 0..200 {
+            let ids = random_ids(seed, 30, 4, 3);
+            assert_eq!(one_apart(&ids), one_apart_by_comparison(&ids), "{:?}", ids);
+        }
+        let ids: Vec<String> = crate::read_file("src/bin/02b/input.txt").collect();
+        assert_eq!(one_apart(&ids), one_apart_by_comparison(&ids));
+    }
+
+    #[test]
+    fn one_apart_large_input_test() {
+        let mut ids = random_ids(7, 20_000, 26, 26);
+        let mut twin: Vec<char> = ids[1234].chars().collect();
+        twin[20] = if twin[20] == 'a' { 'b' } else { 'a' };
+        ids.insert(15_000, twin.into_iter().collect());
+        assert_eq!(one_apart(&ids), Some((1234, 15_000, 20)));
+    }
+
+    fn strings(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn all_one_apart_test() {
+        let ids = strings(&["abc", "abd", "xbd", "abc", "abz"]);
+        assert_eq!(all_one_apart(&ids), vec![(0, 1, 2), (0, 4, 2), (1, 2, 0), (1, 3, 2), (1, 4, 2), (3, 4, 2)]);
+    }
+
+    #[test]
+    fn find_pair_test() {
+        let ids: Vec<String> = ONE_APART_INPUT.lines().map(String::from).collect();
+        let expected = OneApart {
+            ids: ("fghij".to_string(), "fguij".to_string()),
+            lines: (1, 4),
+            position: 2,
+            common: "fgij".to_string(),
+        };
+        assert_eq!(find_pair(&ids), Ok(expected));
+    }
+
+    #[test]
+    fn find_pair_errors_test() {
+        assert_eq!(find_pair(&strings(&["abc", "xyz"])), Err(PairError::NoPair));
+        assert_eq!(find_pair(&[]), Err(PairError::NoPair));
+        assert_eq!(find_pair(&strings(&["abc", "abd", "xyz", "xyw"])), Err(PairError::MultiplePairs(vec![(0, 1), (2, 3)])));
+        let error = find_pair(&strings(&["abc", "abd", "ab"])).unwrap_err();
+        assert_eq!(error, PairError::UnequalLength { line: 2, expected: 3, found: 2, units: Units::default() });
+        assert_eq!(error.to_string(), "Line 3: ID has 2 characters, expected 3");
+    }
+
+    const GRAPHEMES: Units = Units { graphemes: true, normalization: Normalization::Nfc };
+
+    #[test]
+    fn checksum_with_units_test() {
+        // Three "é"s, one of them decomposed, and two "👩‍🔬"s sharing their code points with "👩"
+        let ids = strings(&["e\u{301}\u{e9}\u{e9}x", "\u{1f469}\u{200d}\u{1f52c}\u{1f469}\u{200d}\u{1f52c}\u{1f469}"]);
+        let result = checksum_with_units(ids.iter().cloned(), &[2, 3], Combine::Table, Units::default()).unwrap();
+        assert_eq!((result.count(2), result.count(3)), (2, 1));
+        assert_eq!(result.ids[&3], vec![ids[1].clone()]);
+
+        let result = checksum_with_units(ids.iter().cloned(), &[2, 3], Combine::Table, GRAPHEMES).unwrap();
+        assert_eq!(result.ids[&2], vec![ids[1].clone()]);
+        assert_eq!(result.ids[&3], vec![ids[0].clone()]);
+    }
+
+    #[test]
+    fn find_pair_with_units_test() {
+        // Differ in one grapheme, but in two chars
+        let ids = strings(&["a\u{1f469}\u{200d}\u{1f52c}b", "xyz", "a\u{1f469}\u{200d}\u{1f680}b"]);
+        let error = PairError::UnequalLength { line: 1, expected: 5, found: 3, units: Units::default() };
+        assert_eq!(find_pair_with_units(&ids, Units::default()), Err(error));
+        let expected = OneApart {
+            ids: (ids[0].clone(), ids[2].clone()),
+            lines: (0, 2),
+            position: 1,
+            common: "ab".to_string(),
+        };
+        assert_eq!(find_pair_with_units(&ids, GRAPHEMES), Ok(expected));
+        let error = find_pair_with_units(&strings(&["a\u{1f469}\u{200d}\u{1f52c}b", "xy"]), GRAPHEMES).unwrap_err();
+        assert_eq!(error.to_string(), "Line 2: ID has 2 grapheme clusters, expected 3");
+    }
+
+    #[test]
+    fn find_pair_with_normalization_test() {
+        // The same word, once decomposed, and a word one letter away
+        let ids = strings(&["cafe\u{301}s", "caf\u{e9}t", "zzzzz"]);
+        let error = PairError::UnequalLength { line: 1, expected: 6, found: 5, units: Units::default() };
+        assert_eq!(find_pair_with_units(&ids, Units::default()), Err(error));
+        let result = find_pair_with_units(&ids, Units { graphemes: false, normalization: Normalization::Nfc }).unwrap();
+        assert_eq!((result.lines, result.position, result.common.as_str()), ((0, 1), 4, "caf\u{e9}"));
+        assert_eq!(result.ids, (ids[0].clone(), ids[1].clone()));
+    }
+
+    #[test]
+    fn combine_from_str_test() {
+        assert_eq!("sum".parse(), Ok(Combine::Sum));
+        assert_eq!("max".parse::<Combine>(), Err(ChecksumError::UnknownCombine("max".to_string())));
+    }
+}
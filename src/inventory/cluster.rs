@@ -0,0 +1,112 @@
+use crate::union_find::UnionFind;
+use super::BkTree;
+
+pub const WILDCARD: char = '?';
+
+// `pattern` keeps the letters all members share and has a `WILDCARD` everywhere else,
+// including past the end of the shorter IDs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub lines: Vec<usize>,
+    pub pattern: String,
+}
+
+// Clusters of two or more IDs, largest first, and the lines of the IDs not linked to any other
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterReport {
+    pub clusters: Vec<Cluster>,
+    pub outliers: Vec<usize>,
+}
+
+// Groups IDs connected by chains of IDs at most `k` letters apart
+pub fn clusters(ids: &[String], k: usize) -> ClusterReport {
+    let mut sets = UnionFind::new(ids.len());
+    for (a, b, _) in BkTree::new(ids).pairs_within(k) {
+        sets.union(a, b);
+    }
+
+    let (mut clusters, mut outliers) = (Vec::new(), Vec::new());
+    for lines in sets.sets() {
+        match lines.as_slice() {
+            [line] => outliers.push(*line),
+            _ => {
+                let pattern = pattern(lines.iter().map(|&line| ids[line].as_str()));
+                clusters.push(Cluster { lines, pattern });
+            }
+        }
+    }
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.lines.len()));
+    ClusterReport { clusters, outliers }
+}
+
+fn pattern<'a>(ids: impl Iterator<Item=&'a str>) -> String {
+    let mut pattern: Vec<Option<char>> = Vec::new();
+    for (i, id) in ids.enumerate() {
+        let letters: Vec<char> = id.chars().collect();
+        if i == 0 {
+            pattern = letters.into_iter().map(Some).collect();
+            continue;
+        }
+        if letters.len() > pattern.len() {
+            pattern.resize(letters.len(), None);
+        }
+        for (position, shared) in pattern.iter_mut().enumerate() {
+            if letters.get(position) != shared.as_ref() {
+                *shared = None;
+            }
+        }
+    }
+    pattern.into_iter().map(|letter| letter.unwrap_or(WILDCARD)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::hamming;
+    use pretty_assertions::assert_eq;
+
+    fn strings(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn clusters_test() {
+        let ids = strings(&["abcde", "xyzzy", "abcdf", "qqqqq", "abxdf", "xyzzz", "zzzzz"]);
+        let expected = ClusterReport {
+            clusters: vec![
+                Cluster { lines: vec![0, 2, 4], pattern: "ab?d?".to_string() },
+                Cluster { lines: vec![1, 5], pattern: "xyzz?".to_string() },
+            ],
+            outliers: vec![3, 6],
+        };
+        assert_eq!(clusters(&ids, 1), expected);
+
+        let report = clusters(&ids, 2);
+        assert_eq!(report.clusters[1], Cluster { lines: vec![1, 5, 6], pattern: "??zz?".to_string() });
+        assert_eq!(report.outliers, vec![3]);
+    }
+
+    #[test]
+    fn clusters_unequal_lengths_test() {
+        let report = clusters(&strings(&["abc", "abcd", "abc"]), 1);
+        assert_eq!(report.clusters, vec![Cluster { lines: vec![0, 1, 2], pattern: "abc?".to_string() }]);
+        assert_eq!(clusters(&[], 1), ClusterReport { clusters: vec![], outliers: vec![] });
+    }
+
+    // Every linked pair ends up in the same cluster and only IDs without links are outliers
+    #[test]
+    fn clusters_match_links_test() {
+        let ids: Vec<String> = crate::read_file("src/bin/02b/input.txt").collect();
+        let report = clusters(&ids, 2);
+        let cluster_of = |line: usize| report.clusters.iter().position(|cluster| cluster.lines.contains(&line));
+        let letters: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+        for a in 0..ids.len() {
+            let linked: Vec<usize> = (0..ids.len()).filter(|&b| b != a && hamming(&letters[a], &letters[b]) <= 2).collect();
+            assert_eq!(linked.is_empty(), report.outliers.contains(&a));
+            for b in linked {
+                assert_eq!(cluster_of(a), cluster_of(b));
+            }
+        }
+        assert_eq!(report.clusters.iter().map(|cluster| cluster.lines.len()).sum::<usize>() + report.outliers.len(), ids.len());
+    }
+}
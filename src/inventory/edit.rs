@@ -0,0 +1,285 @@
+use std::{
+    cell::Cell,
+    error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use super::BkTree;
+
+// `Damerau` is the optimal string alignment distance: adjacent transpositions count as one edit,
+// but a transposed pair isn't edited again
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Metric {
+    Levenshtein,
+    Damerau,
+}
+
+impl FromStr for Metric {
+    type Err = MetricError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "levenshtein" => Ok(Metric::Levenshtein),
+            "damerau" => Ok(Metric::Damerau),
+            _ => Err(MetricError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricError(String);
+
+impl Display for MetricError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown edit distance '{}', expected levenshtein or damerau", self.0)
+    }
+}
+
+impl error::Error for MetricError {}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Edit {
+    Keep(char),
+    Substitute(char, char),
+    Insert(char),
+    Delete(char),
+    Transpose(char, char),
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Edit::Keep(letter) => write!(f, "{}", letter),
+            Edit::Substitute(from, to) => write!(f, "[{}>{}]", from, to),
+            Edit::Insert(letter) => write!(f, "[+{}]", letter),
+            Edit::Delete(letter) => write!(f, "[-{}]", letter),
+            Edit::Transpose(first, second) => write!(f, "[{}{}>{}{}]", first, second, second, first),
+        }
+    }
+}
+
+pub fn format_script(script: &[Edit]) -> String {
+    script.iter().map(Edit::to_string).collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditPair {
+    pub lines: (usize, usize),
+    pub distance: usize,
+    pub script: Vec<Edit>,
+}
+
+impl Metric {
+    pub fn distance(self, a: &[char], b: &[char]) -> usize {
+        let table = self.table(a, b);
+        table[a.len()][b.len()]
+    }
+
+    // `table[i][j]` is the distance between the first `i` letters of `a` and the first `j` of `b`
+    fn table(self, a: &[char], b: &[char]) -> Vec<Vec<usize>> {
+        let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for i in 0..=a.len() {
+            for j in 0..=b.len() {
+                table[i][j] = match (i, j) {
+                    (0, _) => j,
+                    (_, 0) => i,
+                    _ => {
+                        let mut best = (table[i - 1][j] + 1)
+                            .min(table[i][j - 1] + 1)
+                            .min(table[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]));
+                        if self.transposes(a, b, i, j) {
+                            best = best.min(table[i - 2][j - 2] + 1);
+                        }
+                        best
+                    }
+                };
+            }
+        }
+        table
+    }
+
+    fn transposes(self, a: &[char], b: &[char], i: usize, j: usize) -> bool {
+        self == Metric::Damerau && i > 1 && j > 1
+            && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] && a[i - 1] != b[j - 1]
+    }
+
+    // The edits turning `a` into `b`, one of the shortest scripts
+    pub fn script(self, a: &[char], b: &[char]) -> Vec<Edit> {
+        let table = self.table(a, b);
+        let mut script = Vec::new();
+        let (mut i, mut j) = (a.len(), b.len());
+        while i > 0 || j > 0 {
+            let here = table[i][j];
+            if i > 0 && j > 0 && a[i - 1] == b[j - 1] && here == table[i - 1][j - 1] {
+                script.push(Edit::Keep(a[i - 1]));
+                (i, j) = (i - 1, j - 1);
+            } else if i > 0 && j > 0 && here == table[i - 1][j - 1] + 1 {
+                script.push(Edit::Substitute(a[i - 1], b[j - 1]));
+                (i, j) = (i - 1, j - 1);
+            } else if self.transposes(a, b, i, j) && here == table[i - 2][j - 2] + 1 {
+                script.push(Edit::Transpose(a[i - 2], a[i - 1]));
+                (i, j) = (i - 2, j - 2);
+            } else if i > 0 && here == table[i - 1][j] + 1 {
+                script.push(Edit::Delete(a[i - 1]));
+                i -= 1;
+            } else {
+                script.push(Edit::Insert(b[j - 1]));
+                j -= 1;
+            }
+        }
+        script.reverse();
+        script
+    }
+}
+
+pub fn levenshtein(a: &[char], b: &[char]) -> usize {
+    Metric::Levenshtein.distance(a, b)
+}
+
+pub fn damerau(a: &[char], b: &[char]) -> usize {
+    Metric::Damerau.distance(a, b)
+}
+
+// All pairs of different IDs at the smallest distance there is, with the script from the earlier to the later ID.
+// Every ID is looked up among the earlier ones in a BK-tree over the Levenshtein distance, which is
+// a metric, unlike the Damerau distance used here, with the best distance so far as the radius. Since a transposition
+// is two Levenshtein edits, pairs within Damerau distance `k` are all within Levenshtein distance `2k`
+pub fn closest_pairs(ids: &[String], metric: Metric) -> Vec<EditPair> {
+    let letters: Vec<Vec<char>> = ids.iter().map(|id| id.chars().collect()).collect();
+    let mut tree = BkTree::empty(ids, levenshtein);
+    let radius = |best: usize| match metric {
+        Metric::Levenshtein => best,
+        Metric::Damerau => best.saturating_mul(2),
+    };
+
+    let best = Cell::new(usize::MAX);
+    let mut pairs = Vec::new();
+    for (b, id) in letters.iter().enumerate() {
+        tree.search(id, |a, levenshtein_distance| {
+            if levenshtein_distance == 0 || levenshtein_distance > radius(best.get()) {
+                return;
+            }
+            let distance = match metric {
+                Metric::Levenshtein => levenshtein_distance,
+                Metric::Damerau => damerau(&letters[a], id),
+            };
+            if distance < best.get() {
+                best.set(distance);
+                pairs.clear();
+            }
+            if distance == best.get() {
+                pairs.push((a, b));
+            }
+        }, || radius(best.get()));
+        tree.insert(b);
+    }
+    pairs.sort_unstable();
+    pairs.into_iter()
+        .map(|(a, b)| EditPair { lines: (a, b), distance: best.get(), script: metric.script(&letters[a], &letters[b]) })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use test_case::test_case;
+
+    fn letters(id: &str) -> Vec<char> {
+        id.chars().collect()
+    }
+
+    #[test_case("kitten", "sitting", 3, 3)]
+    #[test_case("abcd", "acbd", 2, 1)]
+    #[test_case("ca", "abc", 3, 3)]
+    #[test_case("", "abc", 3, 3)]
+    #[test_case("abc", "abc", 0, 0)]
+    #[test_case("abcdef", "abdef", 1, 1)]
+    fn distance_test(a: &str, b: &str, levenshtein_distance: usize, damerau_distance: usize) {
+        pretty_assertions::assert_eq!(levenshtein(&letters(a), &letters(b)), levenshtein_distance);
+        pretty_assertions::assert_eq!(damerau(&letters(a), &letters(b)), damerau_distance);
+    }
+
+    #[test_case("kitten", "sitting", Metric::Levenshtein, "[k>s]itt[e>i]n[+g]")]
+    #[test_case("abcd", "acbd", Metric::Damerau, "a[bc>cb]d")]
+    #[test_case("abcdef", "abdef", Metric::Levenshtein, "ab[-c]def")]
+    #[test_case("abdef", "abcdef", Metric::Damerau, "ab[+c]def")]
+    fn script_test(a: &str, b: &str, metric: Metric, expected: &str) {
+        pretty_assertions::assert_eq!(format_script(&metric.script(&letters(a), &letters(b))), expected);
+    }
+
+    // Applying a script must give back the second ID, in as many edits as the distance
+    #[test]
+    fn script_is_shortest_test() {
+        let mut generator = Random::new(44);
+        let mut random = move |bound: u64| generator.below(bound);
+        for _ in 0..500 {
+            let a: Vec<char> = (0..random(7)).map(|_| (b'a' + random(3) as u8) as char).collect();
+            let b: Vec<char> = (0..random(7)).map(|_| (b'a' + random(3) as u8) as char).collect();
+            for metric in [Metric::Levenshtein, Metric::Damerau] {
+                let script = metric.script(&a, &b);
+                let (mut from, mut to) = (String::new(), String::new());
+                for edit in &script {
+                    match *edit {
+                        Edit::Keep(x) => { from.push(x); to.push(x); }
+                        Edit::Substitute(x, y) => { from.push(x); to.push(y); }
+                        Edit::Insert(y) => to.push(y),
+                        Edit::Delete(x) => from.push(x),
+                        Edit::Transpose(x, y) => { from.push(x); from.push(y); to.push(y); to.push(x); }
+                    }
+                }
+                pretty_assertions::assert_eq!((from, to), (a.iter().collect(), b.iter().collect()));
+                let edits = script.iter().filter(|edit| !matches!(edit, Edit::Keep(_))).count();
+                pretty_assertions::assert_eq!(edits, metric.distance(&a, &b));
+            }
+        }
+    }
+
+    fn closest_by_scan(ids: &[String], metric: Metric) -> Vec<(usize, usize, usize)> {
+        let mut pairs = Vec::new();
+        for a in 0..ids.len() {
+            for b in a + 1..ids.len() {
+                let distance = metric.distance(&letters(&ids[a]), &letters(&ids[b]));
+                if distance > 0 {
+                    pairs.push((a, b, distance));
+                }
+            }
+        }
+        let best = pairs.iter().map(|&(_, _, distance)| distance).min();
+        pairs.retain(|&(_, _, distance)| Some(distance) == best);
+        pairs
+    }
+
+    #[test]
+    fn closest_pairs_matches_scan_test() {
+        let mut generator = Random::new(4);
+        let mut random = move |bound: u64| generator.below(bound);
+        for _ in 0..100 {
+            let ids: Vec<String> = (0..random(30))
+                .map(|_| (0..3 + random(5)).map(|_| (b'a' + random(4) as u8) as char).collect())
+                .collect();
+            for metric in [Metric::Levenshtein, Metric::Damerau] {
+                let pairs: Vec<_> = closest_pairs(&ids, metric).into_iter()
+                    .map(|pair| (pair.lines.0, pair.lines.1, pair.distance))
+                    .collect();
+                pretty_assertions::assert_eq!(pairs, closest_by_scan(&ids, metric), "{:?}", ids);
+            }
+        }
+    }
+
+    #[test]
+    fn closest_pairs_test() {
+        let ids: Vec<String> = vec!["abcdef", "abcdef", "badcfe", "abdcef", "abcxyz"].into_iter().map(String::from).collect();
+        let pairs = closest_pairs(&ids, Metric::Damerau);
+        let formatted: Vec<_> = pairs.iter().map(|pair| (pair.lines, format_script(&pair.script))).collect();
+        pretty_assertions::assert_eq!(formatted, vec![((0, 3), "ab[cd>dc]ef".to_string()), ((1, 3), "ab[cd>dc]ef".to_string())]);
+        pretty_assertions::assert_eq!(closest_pairs(&ids, Metric::Levenshtein)[0].distance, 2);
+        pretty_assertions::assert_eq!(closest_pairs(&ids[..1], Metric::Levenshtein), vec![]);
+    }
+
+    #[test]
+    fn metric_from_str_test() {
+        pretty_assertions::assert_eq!("damerau".parse(), Ok(Metric::Damerau));
+        pretty_assertions::assert_eq!("hamming".parse::<Metric>(), Err(MetricError("hamming".to_string())));
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, BinaryHeap};
+use super::hamming;
+
+struct Node {
+    line: usize,
+    children: BTreeMap<usize, usize>,
+}
+
+// BK-tree, over `hamming` unless told otherwise: every child sits at its distance from the parent,
+// so the triangle inequality lets a search skip the subtrees that are too far away.
+// The distance has to be a metric for that to hold
+pub struct BkTree {
+    ids: Vec<Vec<char>>,
+    nodes: Vec<Node>,
+    distance: fn(&[char], &[char]) -> usize,
+}
+
+impl BkTree {
+    pub fn new(ids: &[String]) -> Self {
+        Self::with_distance(ids, hamming)
+    }
+
+    pub fn with_distance(ids: &[String], distance: fn(&[char], &[char]) -> usize) -> Self {
+        let mut tree = Self::empty(ids, distance);
+        for line in 0..tree.ids.len() {
+            tree.insert(line);
+        }
+        tree
+    }
+
+    // Knows the IDs, but holds none of them until they are inserted
+    pub(super) fn empty(ids: &[String], distance: fn(&[char], &[char]) -> usize) -> Self {
+        let ids = ids.iter().map(|id| id.chars().collect()).collect();
+        BkTree { ids, nodes: Vec::new(), distance }
+    }
+
+    pub(super) fn insert(&mut self, line: usize) {
+        let new = self.nodes.len();
+        self.nodes.push(Node { line, children: BTreeMap::new() });
+        if new == 0 {
+            return;
+        }
+        let mut node = 0;
+        loop {
+            let distance = (self.distance)(&self.ids[self.nodes[node].line], &self.ids[line]);
+            match self.nodes[node].children.get(&distance) {
+                Some(&child) => node = child,
+                None => {
+                    self.nodes[node].children.insert(distance, new);
+                    return;
+                }
+            }
+        }
+    }
+
+    // Visits the nodes that can be within `radius()` of `id`, the radius being read again before every node
+    pub(super) fn search(&self, id: &[char], mut visit: impl FnMut(usize, usize), radius: impl Fn() -> usize) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let Node { line, children } = &self.nodes[node];
+            let distance = (self.distance)(&self.ids[*line], id);
+            visit(*line, distance);
+            let radius = radius();
+            let low = distance.saturating_sub(radius);
+            stack.extend(children.range(low..=distance.saturating_add(radius)).map(|(_, &child)| child));
+        }
+    }
+
+    // `(line, distance)` of every ID within `k` of `id`, closest first
+    pub fn within(&self, id: &str, k: usize) -> Vec<(usize, usize)> {
+        let id: Vec<char> = id.chars().collect();
+        let mut result = Vec::new();
+        self.search(&id, |line, distance| {
+            if distance <= k {
+                result.push((line, distance));
+            }
+        }, || k);
+        result.sort_unstable_by_key(|&(line, distance)| (distance, line));
+        result
+    }
+
+    // The `count` IDs closest to `id`, ties going to the earlier line
+    pub fn nearest(&self, id: &str, count: usize) -> Vec<(usize, usize)> {
+        let id: Vec<char> = id.chars().collect();
+        let best = std::cell::RefCell::new(BinaryHeap::new());
+        self.search(&id, |line, distance| {
+            let mut best = best.borrow_mut();
+            best.push((distance, line));
+            if best.len() > count {
+                best.pop();
+            }
+        }, || {
+            let best = best.borrow();
+            if best.len() < count { usize::MAX } else { best.peek().map_or(usize::MAX, |&(distance, _)| distance) }
+        });
+        let mut result: Vec<(usize, usize)> = best.into_inner().into_iter().map(|(distance, line)| (line, distance)).collect();
+        result.sort_unstable_by_key(|&(line, distance)| (distance, line));
+        result
+    }
+
+    // Every pair of lines `(a, b, distance)` with `a < b` and the IDs at most `k` apart
+    pub fn pairs_within(&self, k: usize) -> Vec<(usize, usize, usize)> {
+        let mut pairs = Vec::new();
+        for (a, id) in self.ids.iter().enumerate() {
+            self.search(id, |b, distance| {
+                if b > a && distance <= k {
+                    pairs.push((a, b, distance));
+                }
+            }, || k);
+        }
+        pairs.sort_unstable();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Random;
+    use pretty_assertions::assert_eq;
+
+    fn random_ids(seed: u64, count: usize) -> Vec<String> {
+        let mut generator = Random::new(seed);
+        let mut random = move |bound: u64| generator.below(bound);
+        (0..count).map(|_| {
+            let length = 4 + random(3) as usize;
+            (0..length).map(|_| (b'a' + random(3) as u8) as char).collect()
+        }).collect()
+    }
+
+    fn scan(ids: &[String], id: &str) -> Vec<(usize, usize)> {
+        let id: Vec<char> = id.chars().collect();
+        let mut result: Vec<(usize, usize)> = ids.iter()
+            .map(|other| hamming(&other.chars().collect::<Vec<_>>(), &id))
+            .enumerate()
+            .collect();
+        result.sort_unstable_by_key(|&(line, distance)| (distance, line));
+        result
+    }
+
+    #[test]
+    fn within_test() {
+        let ids: Vec<String> = vec!["abcde", "abcdf", "abxyf", "zzzzz", "abcde"].into_iter().map(String::from).collect();
+        let tree = BkTree::new(&ids);
+        assert_eq!(tree.within("abcde", 0), vec![(0, 0), (4, 0)]);
+        assert_eq!(tree.within("abcde", 1), vec![(0, 0), (4, 0), (1, 1)]);
+        assert_eq!(tree.nearest("abxye", 2), vec![(2, 1), (0, 2)]);
+        assert_eq!(tree.pairs_within(1), vec![(0, 1, 1), (0, 4, 0), (1, 4, 1)]);
+    }
+
+    #[test]
+    fn empty_tree_test() {
+        let tree = BkTree::new(&[]);
+        assert_eq!(tree.within("abc", 3), vec![]);
+        assert_eq!(tree.nearest("abc", 3), vec![]);
+    }
+
+    #[test]
+    fn matches_scan_test() {
+        for seed in 0..50 {
+            let ids = random_ids(seed, 40);
+            let tree = BkTree::new(&ids);
+            for query in random_ids(seed + 1000, 5).iter().chain(&ids[..3]) {
+                let all = scan(&ids, query);
+                for k in 0..4 {
+                    let expected: Vec<_> = all.iter().cloned().filter(|&(_, distance)| distance <= k).collect();
+                    assert_eq!(tree.within(query, k), expected, "{:?} {}", query, k);
+                }
+                for count in [0, 1, 3, 10, 50] {
+                    let expected: Vec<_> = all.iter().cloned().take(count).collect();
+                    assert_eq!(tree.nearest(query, count), expected, "{:?} {}", query, count);
+                }
+            }
+            let expected: Vec<_> = (0..ids.len())
+                .flat_map(|a| scan(&ids, &ids[a]).into_iter().filter(move |&(b, distance)| b > a && distance <= 2).map(move |(b, d)| (a, b, d)))
+                .collect::<std::collections::BTreeSet<_>>().into_iter().collect();
+            assert_eq!(tree.pairs_within(2), expected);
+        }
+    }
+
+    #[test]
+    fn one_apart_pairs_test() {
+        let ids: Vec<String> = crate::read_file("src/bin/02b/input.txt").collect();
+        let pairs: Vec<_> = super::super::all_one_apart(&ids).into_iter().map(|(a, b, _)| (a, b, 1)).collect();
+        let within: Vec<_> = BkTree::new(&ids).pairs_within(1).into_iter().filter(|&(_, _, d)| d == 1).collect();
+        assert_eq!(within, pairs);
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::HashMap,
+    error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Normalization {
+    #[default]
+    None,
+    Nfc,
+    Nfkc,
+}
+
+impl FromStr for Normalization {
+    type Err = UnitsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Normalization::None),
+            "nfc" => Ok(Normalization::Nfc),
+            "nfkc" => Ok(Normalization::Nfkc),
+            _ => Err(UnitsError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitsError(String);
+
+impl Display for UnitsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown normalization '{}', expected none, nfc or nfkc", self.0)
+    }
+}
+
+impl error::Error for UnitsError {}
+
+// What an ID is made of: `char`s by default, or extended grapheme clusters,
+// after normalizing the ID
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Units {
+    pub graphemes: bool,
+    pub normalization: Normalization,
+}
+
+impl Units {
+    pub fn is_chars(&self) -> bool {
+        *self == Units::default()
+    }
+
+    // What the units are called in messages
+    pub fn name(&self) -> &'static str {
+        if self.graphemes { "grapheme clusters" } else { "characters" }
+    }
+
+    pub fn split(&self, id: &str) -> Vec<String> {
+        let id: String = match self.normalization {
+            Normalization::None => id.to_string(),
+            Normalization::Nfc => id.nfc().collect(),
+            Normalization::Nfkc => id.nfkc().collect(),
+        };
+        if self.graphemes {
+            id.graphemes(true).map(String::from).collect()
+        } else {
+            id.chars().map(String::from).collect()
+        }
+    }
+}
+
+// Gives every distinct unit a `char` of its own, so the searches written for
+// `char`s work on any units. Symbols are handed out from 0, skipping the surrogates
+#[derive(Debug, Clone, Default)]
+pub struct Symbols {
+    units: Vec<String>,
+    symbols: HashMap<String, char>,
+}
+
+impl Symbols {
+    pub fn encode(&mut self, units: Vec<String>) -> String {
+        let Symbols { units: known, symbols } = self;
+        units.into_iter().map(|unit| {
+            let next = known.len() as u32;
+            *symbols.entry(unit).or_insert_with_key(|unit| {
+                known.push(unit.clone());
+                char::from_u32(if next < 0xd800 { next } else { next + 0x800 }).unwrap()
+            })
+        }).collect()
+    }
+
+    pub fn decode(&self, symbols: &str) -> String {
+        symbols.chars().map(|symbol| {
+            let index = symbol as u32;
+            self.units[if index < 0xd800 { index } else { index - 0x800 } as usize].as_str()
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    // "é" written as "e" and a combining acute accent
+    const DECOMPOSED: &str = "e\u{301}t\u{e9}";
+
+    #[test]
+    fn split_test() {
+        assert_eq!(Units::default().split(DECOMPOSED).len(), 4);
+        let graphemes = Units { graphemes: true, normalization: Normalization::None };
+        assert_eq!(graphemes.split(DECOMPOSED), vec!["e\u{301}", "t", "\u{e9}"]);
+        let nfc = Units { graphemes: false, normalization: Normalization::Nfc };
+        assert_eq!(nfc.split(DECOMPOSED), vec!["\u{e9}", "t", "\u{e9}"]);
+        let nfkc = Units { graphemes: true, normalization: Normalization::Nfkc };
+        assert_eq!(nfkc.split("\u{fb01}x"), vec!["f", "i", "x"]);
+        assert_eq!(graphemes.split("a\u{1f469}\u{200d}\u{1f52c}b").len(), 3);
+    }
+
+    #[test]
+    fn symbols_test() {
+        let mut symbols = Symbols::default();
+        let units = Units { graphemes: true, normalization: Normalization::Nfc };
+        let a = symbols.encode(units.split(DECOMPOSED));
+        let b = symbols.encode(units.split("t\u{e9}t"));
+        assert_eq!(a, "\u{0}\u{1}\u{0}");
+        assert_eq!(b, "\u{1}\u{0}\u{1}");
+        assert_eq!(symbols.decode(&a), "\u{e9}t\u{e9}");
+    }
+
+    #[test]
+    fn symbols_skip_surrogates_test() {
+        let mut symbols = Symbols::default();
+        let encoded: Vec<String> = (0..0xd810).map(|i| symbols.encode(vec![i.to_string()])).collect();
+        assert_eq!(encoded[0xd800], "\u{e000}");
+        assert_eq!(symbols.decode(&encoded[0xd805]), (0xd805).to_string());
+    }
+
+    #[test]
+    fn normalization_from_str_test() {
+        assert_eq!("nfkc".parse(), Ok(Normalization::Nfkc));
+        assert_eq!("nfd".parse::<Normalization>(), Err(UnitsError("nfd".to_string())));
+    }
+}
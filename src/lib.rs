@@ -1,3 +1,26 @@
+pub mod calibration;
+pub mod cycle;
+pub mod fabric;
+pub mod graph;
+pub mod grid;
+pub mod interval;
+pub mod inventory;
+// Seeded inputs for the randomised tests and the benchmarks
+#[doc(hidden)]
+pub mod random;
+pub mod union_find;
+
+pub use aoc2018_derive::FromRegex;
+
+// `FromRegex` names the regex crate through here, so crates deriving it don't need to depend on it.
+// Naming this crate `aoc2018` inside itself lets its own tests derive it as well
+extern crate self as aoc2018;
+#[doc(hidden)]
+pub mod __private {
+    pub use regex::Regex;
+}
+
+use std::env::args;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Lines, Result};
 use std::iter::Map;
@@ -8,4 +31,73 @@ pub fn read_file(filename: &str) -> FileIterator {
     let file = File::open(filename).unwrap();
     let lines = BufReader::new(file).lines();
     lines.map(|line| line.unwrap())
-}
\ No newline at end of file
+}
+
+pub fn has_flag(names: &[&str]) -> bool {
+    args().skip(1).any(|arg| names.contains(&arg.as_str()))
+}
+
+// Accepts both `--name value` and `--name=value`
+pub fn flag_value(name: &str) -> Option<String> {
+    let mut args = args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::num::ParseIntError;
+
+    #[derive(Debug, PartialEq, FromRegex)]
+    #[regex(r"^(?P<name>[a-z]+) (?P<unit_count>\S+)$")]
+    struct Entry {
+        name: String,
+        unit_count: u32,
+    }
+
+    #[derive(Debug, PartialEq, FromRegex)]
+    #[regex(error = "ShapeLineError")]
+    enum Shape {
+        #[regex(r"^circle (\S+)$")]
+        Circle(u32),
+        #[regex(r"^rect (?P<w>\S+)x(?P<h>\S+)$")]
+        Rect { w: u32, h: u32 },
+        #[regex(r"^point$")]
+        Point,
+    }
+
+    fn int_error(text: &str) -> ParseIntError {
+        text.parse::<u32>().unwrap_err()
+    }
+
+    #[test]
+    fn from_regex_struct_test() {
+        assert_eq!("apple 12".parse(), Ok(Entry { name: "apple".to_string(), unit_count: 12 }));
+        assert_eq!("apple".parse::<Entry>(), Err(EntryError::FormatError));
+        let error = "apple x12".parse::<Entry>().unwrap_err();
+        assert_eq!(error, EntryError::UnitCountError(int_error("x12")));
+        assert_eq!(error.to_string(), "Wrong unit_count: invalid digit found in string");
+    }
+
+    #[test]
+    fn from_regex_enum_test() {
+        assert_eq!("circle 3".parse(), Ok(Shape::Circle(3)));
+        assert_eq!("rect 2x5".parse(), Ok(Shape::Rect { w: 2, h: 5 }));
+        assert_eq!("point".parse(), Ok(Shape::Point));
+        assert_eq!("square 3".parse::<Shape>(), Err(ShapeLineError::FormatError));
+        assert_eq!("circle -3".parse::<Shape>(), Err(ShapeLineError::CircleError(int_error("-3"))));
+        let error = "rect 2xy".parse::<Shape>().unwrap_err();
+        assert_eq!(error, ShapeLineError::RectHError(int_error("y")));
+        assert_eq!(error.to_string(), "Wrong h: invalid digit found in string");
+        assert_eq!(ShapeLineError::FormatError.to_string(), "Wrong line format");
+    }
+}
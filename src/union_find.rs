@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+// Disjoint sets over `0..n` with path halving and union by size
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        Self { parents: (0..n).collect(), sizes: vec![1; n] }
+    }
+
+    pub fn find(&mut self, mut node: usize) -> usize {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        node
+    }
+
+    // Returns false if the nodes were already in the same set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        true
+    }
+
+    pub fn size(&mut self, node: usize) -> usize {
+        let root = self.find(node);
+        self.sizes[root]
+    }
+
+    // Every set, sorted, in the order of their smallest node
+    pub fn sets(&mut self) -> Vec<Vec<usize>> {
+        let mut sets: HashMap<usize, Vec<usize>> = HashMap::new();
+        for node in 0..self.parents.len() {
+            sets.entry(self.find(node)).or_default().push(node);
+        }
+        let mut sets: Vec<Vec<usize>> = sets.into_values().collect();
+        sets.sort_unstable_by_key(|set| set[0]);
+        sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn union_find_test() {
+        let mut sets = UnionFind::new(6);
+        assert!(sets.union(0, 3));
+        assert!(sets.union(4, 3));
+        assert!(sets.union(5, 2));
+        assert!(!sets.union(0, 4));
+        assert_eq!(sets.size(4), 3);
+        assert_eq!(sets.size(1), 1);
+        assert_eq!(sets.find(0), sets.find(4));
+        assert_eq!(sets.sets(), vec![vec![0, 3, 4], vec![1], vec![2, 5]]);
+    }
+}